@@ -0,0 +1,99 @@
+// Timings of the drawing primitives the frames are built from, run with `cargo bench --features watch`
+// (any feature the binary builds with, cargo builds it alongside).
+//
+// Everything draws into a plain Vec<u32> the size of the default window, no window needed.
+// Criterion keeps the results of the previous run in target/criterion and reports the change,
+// so a slower rasterizer shows up as a regression there.
+// The watch frame lives in the binary, `watch_frame` repeats what draw_face and draw_hands
+// draw (same proportions as Layout for the default window) with the library's primitives.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use watchrs::draw::{Canvas, Dimensions, Point};
+use watchrs::font;
+
+// The default window size
+const WIDTH: usize = 1200;
+const HEIGHT: usize = 900;
+
+const BACKGROUND: u32 = 0x00_10_10_10;
+const FOREGROUND: u32 = 0x00_E0_E0_E0;
+const HAND: u32 = 0x00_C0_40_40;
+
+fn canvas(buf: &mut [u32]) -> Canvas<'_> {
+    Canvas::new(buf, Dimensions { width: WIDTH, height: HEIGHT }).expect("buffer is WIDTH x HEIGHT")
+}
+
+fn center() -> Point {
+    Point::new((WIDTH / 2) as isize, (HEIGHT / 2) as isize)
+}
+
+fn primitives(c: &mut Criterion) {
+    let mut buf = vec![BACKGROUND; WIDTH * HEIGHT];
+
+    c.bench_function("draw_filled_circle r=4", |b| {
+        b.iter(|| canvas(&mut buf).draw_filled_circle(black_box(center()), black_box(4), FOREGROUND))
+    });
+    c.bench_function("draw_filled_circle r=40", |b| {
+        b.iter(|| canvas(&mut buf).draw_filled_circle(black_box(center()), black_box(40), FOREGROUND))
+    });
+    // A hand: from the center outwards, as thick as the watch's minute hand
+    c.bench_function("draw_line hand", |b| {
+        let tip = center().polar(-0.7, 330.0);
+        b.iter(|| canvas(&mut buf).draw_line(black_box(center()), black_box(tip), black_box(18), FOREGROUND))
+    });
+    // The worst case: corner to corner and thick
+    c.bench_function("draw_line thick diagonal", |b| {
+        let end = Point::new(WIDTH as isize - 1, HEIGHT as isize - 1);
+        b.iter(|| canvas(&mut buf).draw_line(black_box(Point::new(0, 0)), black_box(end), black_box(40), FOREGROUND))
+    });
+}
+
+/// One whole watch frame: background, frame, dial with ticks and numerals, readout, hands and hub.
+fn watch_frame(canvas: &mut Canvas) {
+    let min_dim = WIDTH.min(HEIGHT);
+    let center = center();
+    let thick = (min_dim as f32 * 0.03).round() as usize;
+    let frame_padding = (min_dim as f32 * 0.04).round() as usize;
+    let dial_radius = (min_dim / 2).saturating_sub(frame_padding * 2);
+    let hour_tick = ((min_dim as f32 * 0.06).round() as usize, thick * 2 / 3);
+    let minute_tick = ((min_dim as f32 * 0.025).round() as usize, thick / 4);
+
+    canvas.clear(BACKGROUND);
+    canvas.draw_frame(frame_padding, thick, FOREGROUND);
+    canvas.draw_circle(center, dial_radius as isize, (thick / 4) as isize, FOREGROUND);
+    for i in 0..60 {
+        let (length, thickness) = if i % 5 == 0 { hour_tick } else { minute_tick };
+        let angle = i as f32 * (TAU / 60.0);
+        canvas.draw_tick(center, dial_radius - length, dial_radius, angle, thickness, FOREGROUND);
+    }
+    let scale = min_dim / 110;
+    for hour in 1..=12 {
+        let label = hour.to_string();
+        let at = center.polar(-FRAC_PI_2 + hour as f32 * (TAU / 12.0), dial_radius as f32 * 0.75);
+        let origin = Point::new(at.x - (font::text_width(&label, scale) / 2) as isize, at.y - (font::text_height(scale) / 2) as isize);
+        canvas.draw_text(origin, &label, scale, FOREGROUND);
+    }
+    let readout_scale = min_dim / 150;
+    let readout_y = center.y + (dial_radius as f32 * 0.35).round() as isize;
+    let text_origin = Point::new(center.x - (font::text_width("10:08:37", readout_scale) / 2) as isize, readout_y);
+    canvas.draw_text(text_origin, "10:08:37", readout_scale, FOREGROUND);
+
+    // 10:08:37, the hands well apart like on a watch ad
+    let hands = [(10.0 / 12.0, 0.5, thick), (8.0 / 60.0, 0.75, thick * 2 / 3), (37.0 / 60.0, 1.0, thick / 3)];
+    for (turn, length, thickness) in hands {
+        let tip = center.polar(-FRAC_PI_2 + turn * TAU, dial_radius as f32 * length);
+        canvas.draw_line(center, tip, thickness, HAND);
+    }
+    canvas.draw_filled_circle(center, thick, FOREGROUND);
+}
+
+fn frames(c: &mut Criterion) {
+    let mut buf = vec![0; WIDTH * HEIGHT];
+    c.bench_function("watch frame", |b| b.iter(|| watch_frame(black_box(&mut canvas(&mut buf)))));
+}
+
+criterion_group!(benches, primitives, frames);
+criterion_main!(benches);
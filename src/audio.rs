@@ -0,0 +1,119 @@
+// Short click sounds for the metronome beat and the timer alert, and the bell notes of the watch's chime.
+// The real thing needs the "audio" feature (rodio), without it the clicks are silently dropped
+// so the rest of the app doesn't need to care whether sound was compiled in.
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "audio")] {
+        use std::time::Duration;
+
+        use log::warn;
+        use rodio::{DeviceSinkBuilder, MixerDeviceSink, Source, source::SineWave};
+
+        const CLICK_HZ: f32 = 1500.0;
+        const CLICK_LENGTH: Duration = Duration::from_millis(30);
+        const CLICK_VOLUME: f32 = 0.5;
+        // The first beat of a measure is higher and louder
+        const ACCENT_HZ: f32 = 2500.0;
+        const ACCENT_VOLUME: f32 = 0.9;
+        // Clicks between the beats are lower and softer, so the beat stays the one you hear.
+        // Only the metronome subdivides its beats, the timer's builds go without.
+        #[cfg(feature = "metronome")]
+        const SUBDIVISION_HZ: f32 = 1000.0;
+        #[cfg(feature = "metronome")]
+        const SUBDIVISION_VOLUME: f32 = 0.25;
+        // A chime's bell notes ring on and die away slowly
+        const BELL_LENGTH: Duration = Duration::from_millis(1800);
+        const BELL_VOLUME: f32 = 0.4;
+        // The octave over the note, a little of it makes the sine sound more like a bell
+        const BELL_OVERTONE: f32 = 0.3;
+
+        /// Plays clicks on the default output device.
+        pub struct Clicker {
+            // None if no output device could be opened, then every click is a no-op
+            sink: Option<MixerDeviceSink>,
+        }
+
+        impl Clicker {
+            pub fn new() -> Self {
+                match DeviceSinkBuilder::open_default_sink() {
+                    Ok(mut sink) => {
+                        sink.log_on_drop(false);
+                        Self { sink: Some(sink) }
+                    }
+                    Err(e) => {
+                        warn!("No audio output, clicks will be silent: {e}");
+                        Self { sink: None }
+                    }
+                }
+            }
+
+            /// A clicker that never opens a device, for drawing frames without sound.
+            pub fn silent() -> Self {
+                Self { sink: None }
+            }
+
+            /// Start a click, `accent` for the downbeat. Returns right away,
+            /// the sound plays on rodio's audio thread.
+            pub fn click(&self, accent: bool) {
+                let (hz, volume) = if accent { (ACCENT_HZ, ACCENT_VOLUME) } else { (CLICK_HZ, CLICK_VOLUME) };
+                self.play(hz, volume);
+            }
+
+            /// Start the softer click of a subdivision, between two beats.
+            #[cfg(feature = "metronome")]
+            pub fn click_subdivision(&self) {
+                self.play(SUBDIVISION_HZ, SUBDIVISION_VOLUME);
+            }
+
+            /// Start a bell note of `hz` that sounds `delay` from now, so a whole tune can be queued at once.
+            #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+            pub fn bell(&self, hz: f32, delay: Duration) {
+                let Some(sink) = &self.sink else {
+                    return;
+                };
+                let bell = SineWave::new(hz)
+                    .mix(SineWave::new(hz * 2.0).amplify(BELL_OVERTONE))
+                    .take_duration(BELL_LENGTH)
+                    .fade_out(BELL_LENGTH)
+                    .amplify(BELL_VOLUME)
+                    .delay(delay);
+                sink.mixer().add(bell);
+            }
+
+            fn play(&self, hz: f32, volume: f32) {
+                let Some(sink) = &self.sink else {
+                    return;
+                };
+                // A short sine burst that fades out, so it ends without a pop
+                let click = SineWave::new(hz)
+                    .take_duration(CLICK_LENGTH)
+                    .fade_out(CLICK_LENGTH)
+                    .amplify(volume);
+                sink.mixer().add(click);
+            }
+        }
+    } else {
+        use std::time::Duration;
+
+        /// Stand-in when built without the "audio" feature: clicks are ignored.
+        pub struct Clicker;
+
+        impl Clicker {
+            pub fn new() -> Self {
+                Self
+            }
+
+            pub fn silent() -> Self {
+                Self
+            }
+
+            pub fn click(&self, _accent: bool) {}
+
+            #[cfg(feature = "metronome")]
+            pub fn click_subdivision(&self) {}
+
+            #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+            pub fn bell(&self, _hz: f32, _delay: Duration) {}
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// The watch's chime (the `[chime]` settings): bells at the top of every hour, and at half past if
+// asked for, a single tone or the Westminster quarters.
+//
+// It goes off when the minute of the live time rolls over into :00 (or :30), found by comparing it
+// with the minute at the previous update. That rings once per rollover however many frames are
+// drawn in the minute, never at startup (there's no previous minute yet) and not when the hands are
+// dragged past the hour, those don't change the live time. Only the shown scene is updated, so the
+// watch chimes while it's on screen and not paused.
+//
+// The Westminster quarters are four bells rung in five "changes". On the hour the last four of them
+// ring and then the hour bell strikes the hour (1 to 12), at half past the second and third ring.
+// The whole tune is handed to the audio thread at once, each note with its delay from the start.
+
+use std::time::Duration;
+
+use crate::audio::Clicker;
+use crate::clock::TimeOfDay;
+use crate::config::{Chime, ChimeStyle};
+
+// The four quarter bells, in E major
+const B3: f32 = 246.94;
+const E4: f32 = 329.63;
+const F_SHARP4: f32 = 369.99;
+const G_SHARP4: f32 = 415.30;
+// The hour bell, an octave under the quarters' E
+const HOUR_BELL: f32 = 164.81;
+// The single style's tone
+const SINGLE_TONE: f32 = 880.0;
+
+const CHANGES: [[f32; 4]; 5] = [
+    [G_SHARP4, F_SHARP4, E4, B3],
+    [E4, G_SHARP4, F_SHARP4, B3],
+    [E4, F_SHARP4, G_SHARP4, E4],
+    [G_SHARP4, E4, F_SHARP4, B3],
+    [B3, F_SHARP4, G_SHARP4, E4],
+];
+// Which changes ring, by index into CHANGES
+const HOUR_CHANGES: [usize; 4] = [1, 2, 3, 4];
+const HALF_HOUR_CHANGES: [usize; 2] = [1, 2];
+
+// From one quarter bell to the next, and the rest after each change
+const NOTE_GAP: Duration = Duration::from_millis(600);
+const CHANGE_REST: Duration = Duration::from_millis(600);
+// From one strike of the hour bell to the next, and the rest before the first
+const STRIKE_GAP: Duration = Duration::from_millis(1500);
+
+/// Rings the chime when the hour (or half hour) comes round.
+pub struct Chimer {
+    settings:    Chime,
+    sound:       bool,          // allowed to open the audio output, not when drawing a single frame
+    clicker:     Option<Clicker>, // opened on the first chime with sound, not for a chime that's off
+    last_minute: Option<(u32, u32)>, // hour and minute at the last update, None before the first
+}
+
+impl Chimer {
+    pub fn new(settings: Chime, sound: bool) -> Self {
+        Self { settings, sound, clicker: None, last_minute: None }
+    }
+
+    /// Check the live time `time`, called on every update: returns the hour and minute when it just
+    /// chimed (and should show that with `visual`), None the rest of the time.
+    pub fn update(&mut self, time: TimeOfDay) -> Option<(u32, u32)> {
+        let minute = time.whole_hours_minutes();
+        let previous = self.last_minute.replace(minute);
+        let rolled_over = previous.is_some() && previous != Some(minute);
+        let due = match minute.1 {
+            0 => self.settings.hourly,
+            30 => self.settings.hourly && self.settings.half_hour,
+            _ => false,
+        };
+        if !(rolled_over && due) {
+            return None;
+        }
+
+        if self.settings.sound && self.sound {
+            let clicker = self.clicker.get_or_insert_with(Clicker::new);
+            for (hz, delay) in tune(self.settings.style, minute) {
+                clicker.bell(hz, delay);
+            }
+        }
+        Some(minute)
+    }
+
+    /// Show the chime on the watch.
+    pub fn visual(&self) -> bool {
+        self.settings.visual
+    }
+}
+
+/// The notes to ring at `hour`:`minute` (a full or half hour), each with its delay from the first.
+fn tune(style: ChimeStyle, (hour, minute): (u32, u32)) -> Vec<(f32, Duration)> {
+    if style == ChimeStyle::Single {
+        return vec![(SINGLE_TONE, Duration::ZERO)];
+    }
+
+    let changes: &[usize] = if minute == 0 { &HOUR_CHANGES } else { &HALF_HOUR_CHANGES };
+    let mut notes = Vec::new();
+    let mut at = Duration::ZERO;
+    for &change in changes {
+        for hz in CHANGES[change] {
+            notes.push((hz, at));
+            at += NOTE_GAP;
+        }
+        at += CHANGE_REST;
+    }
+    if minute == 0 {
+        // Struck as on a 12-hour dial, 0 and 12 both twelve times
+        let strikes = match hour % 12 { 0 => 12, h => h };
+        at += STRIKE_GAP - CHANGE_REST;
+        for _ in 0..strikes {
+            notes.push((HOUR_BELL, at));
+            at += STRIKE_GAP;
+        }
+    }
+    notes
+}
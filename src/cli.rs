@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveTime, Utc};
+
+#[cfg(feature = "watch")]
+use chrono_tz::Tz;
+use log::warn;
+
+use crate::config::Settings;
+use crate::share;
+
+/// Command-line options. Everything is optional, anything not given comes from the settings file.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Draw one frame into this PNG file and quit instead of opening a window.
+    pub render: Option<PathBuf>,
+    /// The time of day to draw with --render instead of the current one, today in local time.
+    pub at: Option<DateTime<Utc>>,
+    /// Show one frame and exit, for scripts and screenshots.
+    pub once: bool,
+    /// Print frame times to stderr once a second.
+    pub stats: bool,
+    /// Settings from a config string (the X key prints one), used instead of the saved ones.
+    pub config_string: Option<Settings>,
+    /// Open a fullscreen window on every monitor instead of a single window.
+    pub per_monitor: bool,
+    /// Window icon to use instead of the embedded one.
+    pub icon: Option<PathBuf>,
+    /// Keep the window above the others, remembered for the next launch like the O key.
+    pub always_on_top: bool,
+    /// No title bar or borders, remembered for the next launch like the B key.
+    pub borderless: bool,
+    /// See-through background, remembered for the next launch.
+    pub transparent: bool,
+    /// Wake only when the seconds change, remembered for the next launch.
+    pub power_saving: bool,
+    /// Lower the frame rate on battery, remembered for the next launch.
+    #[cfg(feature = "battery-saver")]
+    pub battery_saver: bool,
+    #[cfg(feature = "watch")]
+    pub fps: Option<u32>,
+    /// NTP server to correct the system clock by, asked once at startup.
+    #[cfg(feature = "ntp")]
+    pub ntp: Option<String>,
+    /// Time zone the watch shows instead of local time.
+    #[cfg(feature = "watch")]
+    pub tz: Option<Tz>,
+    #[cfg(feature = "metronome")]
+    pub bpm: Option<u32>,
+    /// Time zone names for the world clock, replacing the configured ones.
+    #[cfg(feature = "world")]
+    pub world: Option<Vec<String>>,
+    /// Countdown duration in seconds.
+    #[cfg(feature = "timer")]
+    pub timer: Option<u32>,
+}
+
+impl Args {
+    /// Parse the process arguments. Prints `--help` and exits if asked to.
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parse a list of arguments (without the program name).
+    ///
+    /// Accepts both `--flag value` and `--flag=value`. Bad values and unknown flags are
+    /// reported on stderr and ignored, so a typo never keeps the clock from starting.
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Split "--flag=value" into its two halves, "--flag value" takes the next argument
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next());
+
+            match flag.as_str() {
+                "-h" | "--help" => {
+                    print_help();
+                    std::process::exit(0);
+                }
+                "--render" => match value() {
+                    Some(path) => parsed.render = Some(PathBuf::from(path)),
+                    None => warn!("{flag} needs a file name"),
+                },
+                "--at" => parsed.at = parse_time_of_day(&flag, value()),
+                "--once" => parsed.once = true,
+                "--stats" => parsed.stats = true,
+                "--config-string" => parsed.config_string = parse_config_string(&flag, value()),
+                "--per-monitor" => parsed.per_monitor = true,
+                "--always-on-top" => parsed.always_on_top = true,
+                "--borderless" => parsed.borderless = true,
+                "--transparent" => parsed.transparent = true,
+                "--power-saving" => parsed.power_saving = true,
+                #[cfg(feature = "battery-saver")]
+                "--battery-saver" => parsed.battery_saver = true,
+                "--icon" => match value() {
+                    Some(path) => parsed.icon = Some(PathBuf::from(path)),
+                    None => warn!("{flag} needs a file name"),
+                },
+                #[cfg(feature = "watch")]
+                "--fps" => parsed.fps = parse_number(&flag, value()),
+                #[cfg(feature = "ntp")]
+                "--ntp" => match value() {
+                    Some(server) => parsed.ntp = Some(server),
+                    None => warn!("{flag} needs a server, e.g. pool.ntp.org"),
+                },
+                #[cfg(feature = "watch")]
+                "--tz" => parsed.tz = parse_zone(&flag, value()),
+                #[cfg(feature = "world")]
+                "--world" => match value() {
+                    Some(list) => {
+                        let zones = list.split(',').map(str::trim).filter(|zone| !zone.is_empty());
+                        parsed.world = Some(zones.map(String::from).collect());
+                    }
+                    None => warn!("{flag} needs a comma-separated list of time zones"),
+                },
+                #[cfg(feature = "metronome")]
+                "--bpm" => parsed.bpm = parse_number(&flag, value()),
+                #[cfg(feature = "timer")]
+                "--timer" => parsed.timer = parse_duration(&flag, value()),
+                _ => warn!("Unknown argument '{flag}', see --help"),
+            }
+        }
+        parsed
+    }
+}
+
+/// Parse a flag's value as a whole number, reporting a missing or malformed one.
+fn parse_number(flag: &str, value: Option<String>) -> Option<u32> {
+    let Some(value) = value else {
+        warn!("{flag} needs a value");
+        return None;
+    };
+    match value.parse() {
+        Ok(number) => Some(number),
+        Err(_) => {
+            warn!("{flag} expects a whole number, got '{value}'");
+            None
+        }
+    }
+}
+
+/// Parse a flag's value as a config string from the X key, reporting a missing or malformed one.
+fn parse_config_string(flag: &str, value: Option<String>) -> Option<Settings> {
+    let Some(value) = value else {
+        warn!("{flag} needs a config string, the X key prints one");
+        return None;
+    };
+    match share::import(&value) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            warn!("Ignoring {flag}, {e}");
+            None
+        }
+    }
+}
+
+/// Parse a flag's value as an IANA time zone name like `America/New_York`.
+/// An unknown name is reported and the watch keeps local time.
+#[cfg(feature = "watch")]
+fn parse_zone(flag: &str, value: Option<String>) -> Option<Tz> {
+    let Some(value) = value else {
+        warn!("{flag} needs a time zone name");
+        return None;
+    };
+    match value.parse() {
+        Ok(zone) => Some(zone),
+        Err(_) => {
+            warn!("Unknown time zone '{value}', showing local time");
+            None
+        }
+    }
+}
+
+/// Parse a flag's value as a duration in whole seconds, e.g. `90`, `90s`, `5m`, `1h` or `1m30s`.
+/// A number without a unit is seconds.
+#[cfg(feature = "timer")]
+fn parse_duration(flag: &str, value: Option<String>) -> Option<u32> {
+    let Some(value) = value else {
+        warn!("{flag} needs a value");
+        return None;
+    };
+    let invalid = || {
+        warn!("{flag} expects a duration like 90s, 5m or 1m30s, got '{value}'");
+        None
+    };
+    let mut seconds: u32 = 0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return invalid();
+    }
+    // One number and its unit at a time
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len()));
+        let mut tail = tail.chars();
+        let unit = match tail.next() {
+            None | Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 3600,
+            Some(_) => return invalid(),
+        };
+        let part = number.parse::<u32>().ok().and_then(|n| n.checked_mul(unit));
+        match part.and_then(|part| seconds.checked_add(part)) {
+            Some(total) => seconds = total,
+            None => return invalid(),
+        }
+        rest = tail.as_str();
+    }
+    Some(seconds)
+}
+
+/// Parse a flag's value as a time of day like `10:10` or `10:10:30`, today in local time.
+fn parse_time_of_day(flag: &str, value: Option<String>) -> Option<DateTime<Utc>> {
+    let Some(value) = value else {
+        warn!("{flag} needs a time like 10:10 or 10:10:30");
+        return None;
+    };
+    let Some(time) = ["%H:%M:%S", "%H:%M"].iter().find_map(|format| NaiveTime::parse_from_str(&value, format).ok()) else {
+        warn!("{flag} expects a time like 10:10 or 10:10:30, got '{value}'");
+        return None;
+    };
+    // A time skipped by a daylight saving change doesn't exist today
+    match Local::now().date_naive().and_time(time).and_local_timezone(Local).earliest() {
+        Some(time) => Some(time.to_utc()),
+        None => {
+            warn!("{value} doesn't exist today in local time, drawing the current time");
+            None
+        }
+    }
+}
+
+/// Keep `value` only if it lies in `min..=max`, otherwise report it and return `None`.
+pub fn in_range(flag: &str, value: Option<u32>, min: u32, max: u32) -> Option<u32> {
+    let value = value?;
+    if (min..=max).contains(&value) {
+        Some(value)
+    } else {
+        warn!("{flag} {value} is out of range ({min}..={max}), ignoring it");
+        None
+    }
+}
+
+fn print_help() {
+    println!("Usage: WATCHRS [OPTIONS]");
+    println!();
+    println!("Options:");
+    #[cfg(feature = "watch")]
+    println!("  --fps <N>         redraws per second of the seconds hand");
+    #[cfg(feature = "watch")]
+    println!("  --tz <ZONE>       show the time in this zone, e.g. America/New_York");
+    #[cfg(feature = "ntp")]
+    println!("  --ntp <SERVER>    correct the shown time by an NTP server, e.g. pool.ntp.org");
+    #[cfg(feature = "world")]
+    println!("  --world <ZONES>   world clock zones, e.g. Europe/London,Asia/Tokyo");
+    #[cfg(feature = "metronome")]
+    println!("  --bpm <N>         metronome tempo in beats per minute");
+    #[cfg(feature = "timer")]
+    println!("  --timer <TIME>    countdown duration, e.g. 90s, 5m or 1m30s");
+    println!("  --stats           print min/avg/max frame times every second");
+    println!("  --config-string <S>  start with the settings from a string the X key printed, kept like any change");
+    println!("  --per-monitor     a fullscreen window on every monitor, Esc closes one");
+    println!("  --always-on-top   keep the window above other windows (O toggles it)");
+    println!("  --borderless      no title bar or borders, drag the window to move it (B toggles it)");
+    println!("  --transparent     see-through background, only the clock is drawn (X11 with a compositor)");
+    println!("  --power-saving    redraw a ticking watch only when the second changes");
+    #[cfg(feature = "battery-saver")]
+    println!("  --battery-saver   the same while running on battery, the FPS setting on AC");
+    println!("  --icon <FILE>     window icon image, the built-in one if it can't be loaded");
+    println!("  --render <FILE>   draw one frame into a PNG file and exit");
+    println!("  --at <TIME>       with --render, draw this time of day instead, e.g. 10:10:30");
+    println!("  --once            show one frame in the window and exit (--render does anyway)");
+    println!("  -h, --help        print this help");
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Local, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::time_source::{SystemClock, TimeSource};
+
+const SECONDS_PER_DAY: f64 = 24.0 * 3600.0;
+
+/// Time of day as fractional hours, minutes and seconds.
+///
+/// Each field already contains the fraction of the smaller units, so e.g. at 10:30:15
+/// `hours` is 10.504, `minutes` is 30.25 and `seconds` is 15.0.
+/// This is exactly what the hands need: a minute hand that creeps along with the seconds.
+///
+/// The fields are worked out in f64 from the seconds since midnight and only then narrowed to f32,
+/// which still resolves `seconds` to a few microseconds. A day's worth of seconds in f32 would only
+/// resolve to 8 ms and make a sweeping hand stutter. Each field stays below its wrap-around
+/// (60, 60, 24) even where rounding to f32 would reach it, so the readout never shows a 60th second
+/// and the hands go from 59.99999 straight on to 0 at the next second, without a step back or
+/// a duplicate.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TimeOfDay {
+    pub hours:   f32,   // 0..24
+    pub minutes: f32,   // 0..60
+    pub seconds: f32,   // 0..60
+}
+
+impl TimeOfDay {
+    /// Read the time in `zone`, or the system local time without one.
+    pub fn now_in(zone: Option<Tz>) -> Self {
+        Self::at(SystemClock.wall(), zone)
+    }
+
+    /// The time of day at `time` in `zone`, or in the system local time without one.
+    pub fn at(time: DateTime<Utc>, zone: Option<Tz>) -> Self {
+        match zone {
+            Some(zone) => Self::from_time(&time.with_timezone(&zone)),
+            None => Self::from_time(&time.with_timezone(&Local)),
+        }
+    }
+
+    fn from_time(time: &impl Timelike) -> Self {
+        // Sub-second part, so the seconds hand can move smoothly between whole seconds.
+        // (nanosecond() can go above 1e9 during a leap second, clamp it away)
+        let fraction = time.nanosecond().min(999_999_999) as f64 / 1e9;
+        Self::from_seconds(time.num_seconds_from_midnight() as f64 + fraction)
+    }
+
+    /// Time of day `seconds` since midnight, wrapped into one day.
+    pub fn from_seconds(seconds: f64) -> Self {
+        let seconds = seconds.rem_euclid(SECONDS_PER_DAY);
+        Self {
+            hours:   narrow_below(seconds / 3600.0, 24.0),
+            minutes: narrow_below((seconds / 60.0) % 60.0, 60.0),
+            seconds: narrow_below(seconds % 60.0, 60.0),
+        }
+    }
+
+    /// The whole hour and minute. Taken from the difference of neighbouring fields: just before
+    /// a full hour `hours` alone can already have rounded up to it (10:59:59.9999 has `hours` 11.0).
+    pub fn whole_hours_minutes(self) -> (u32, u32) {
+        let (hours, minutes, seconds) = (self.hours as f64, self.minutes as f64, self.seconds as f64);
+        ((hours - minutes / 60.0).round() as u32, (minutes - seconds / 60.0).round() as u32)
+    }
+
+    /// Seconds since midnight, as exact as the fields allow.
+    fn seconds_since_midnight(self) -> f64 {
+        let (hours, minutes) = self.whole_hours_minutes();
+        (hours * 3600 + minutes * 60) as f64 + self.seconds as f64
+    }
+
+    /// The time `seconds` later (earlier if negative), wrapping around midnight.
+    pub fn offset(self, seconds: f32) -> Self {
+        Self::from_seconds(self.seconds_since_midnight() + seconds as f64)
+    }
+
+    /// Digital readout, "HH:MM:SS" or "HH:MM:SS AM/PM" on a 12-hour clock.
+    pub fn format(&self, twelve_hour: bool) -> String {
+        let ((h, m), s) = (self.whole_hours_minutes(), self.seconds as u32);
+        if twelve_hour {
+            let suffix = if h < 12 { "AM" } else { "PM" };
+            // 0 and 12 both show as 12
+            let h = match h % 12 { 0 => 12, h => h };
+            format!("{h:02}:{m:02}:{s:02} {suffix}")
+        } else {
+            format!("{h:02}:{m:02}:{s:02}")
+        }
+    }
+}
+
+/// `value` (in 0..limit) as an f32, kept below `limit` where rounding would land on it.
+fn narrow_below(value: f64, limit: f32) -> f32 {
+    (value as f32).min(limit.next_down())
+}
+
+/// The date at `time` in `zone`, or in the system local time without one.
+pub fn date_at(time: DateTime<Utc>, zone: Option<Tz>) -> NaiveDate {
+    match zone {
+        Some(zone) => time.with_timezone(&zone).date_naive(),
+        None => time.with_timezone(&Local).date_naive(),
+    }
+}
+
+/// The zone's abbreviation right now, e.g. "EST" or "EDT" for America/New_York.
+/// Zones without a common one get their UTC offset instead, like "+03".
+pub fn zone_abbreviation(zone: Tz) -> String {
+    SystemClock.wall().with_timezone(&zone).format("%Z").to_string()
+}
+
+/// Guess whether the user's locale writes times on a 12-hour clock.
+///
+/// There's no portable API for this, so it looks at the usual locale environment variables
+/// (LC_ALL, LC_TIME, LANG) and checks them against regions known to use 12 hours.
+/// Anything unknown or unset means 24 hours.
+pub fn locale_prefers_12_hour() -> bool {
+    const TWELVE_HOUR_LOCALES: [&str; 8] = ["en_US", "en_CA", "en_AU", "en_NZ", "en_PH", "en_IN", "hi_IN", "es_MX"];
+
+    let locale = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => TWELVE_HOUR_LOCALES.iter().any(|prefix| locale.starts_with(prefix)),
+        None => false,
+    }
+}
@@ -0,0 +1,118 @@
+// Complications: small extras on the watch dial besides the time. The day of the month (and the
+// weekday, if chosen) sits in a date window at 3, 6 or 9 o'clock, the phase of the moon is a small
+// moon at 9 o'clock, or at 3 when the date window takes 9. Each is turned on in the settings or with
+// its key (D and M), see `Complications`.
+//
+// Both change once a day at most, so they're drawn into the watch's background snapshot with
+// the rest of the face, and the watch draws that again when the day changes. The hands pass over them.
+
+use std::f32::consts::PI;
+use std::f64::consts::TAU;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+use crate::config::{Complications, DatePosition};
+use watchrs::draw::{self, Canvas, Dimensions, Point};
+use watchrs::font;
+use crate::layout::Layout;
+use crate::theme::ThemePalette;
+
+// How far out from the center the complications sit, as a fraction of the dial radius
+const DISTANCE: f32 = 0.5;
+// Outer edge of a date window at 3 or 9 o'clock, as a fraction of the dial radius. Clear of the
+// numerals, a window with the weekday is wider and reaches further in instead.
+const DATE_OUTER_EDGE: f32 = 0.62;
+// Brightness of the date window's inside against the background, a little darker like a cutout in the dial
+const DATE_WINDOW_SHADE: f32 = 0.8;
+// Average time from one new moon to the next, in days
+const SYNODIC_MONTH: f64 = 29.530588853;
+// A known new moon to count from: 2000-01-06 18:14 UTC, in seconds since the Unix epoch
+const REFERENCE_NEW_MOON: i64 = 947_182_440;
+
+/// Draw the complications that are on: the date of `today` and the moon phase at `now`.
+pub fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, complications: Complications, today: NaiveDate, now: DateTime<Utc>) {
+    if complications.date {
+        draw_date(canvas, layout, palette, complications, today);
+    }
+    if complications.moon_phase {
+        // Out of the date window's way
+        let at_nine = complications.date && complications.date_position == DatePosition::Nine;
+        draw_moon(canvas, layout, palette, moon_phase(now), if at_nine { 0.0 } else { PI });
+    }
+}
+
+/// Text scale and padding of the date box, the moon is as tall as the box.
+fn box_scale(layout: &Layout) -> (usize, usize) {
+    let scale = layout.watch.readout_scale;
+    (scale, scale * 2)
+}
+
+/// The date window: the day of the month, after the weekday with `complications.weekday` ("WED 12"),
+/// centered in a box at `complications.date_position`.
+///
+/// At 3 or 9 o'clock the box reaches in from DATE_OUTER_EDGE. At 6 it sits under the digital readout,
+/// in the gap above the 6, where its text is two thirds the size to fit. So is the text with the
+/// weekday, which would reach into the hub otherwise. The hands pass over the box.
+fn draw_date(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, complications: Complications, today: NaiveDate) {
+    let (scale, padding) = if complications.weekday || complications.date_position == DatePosition::Six {
+        let scale = (layout.watch.readout_scale * 2 / 3).max(1);
+        (scale, scale * 2)
+    } else {
+        box_scale(layout)
+    };
+    // Sized for the widest text, so the box doesn't change size from the 9th to the 10th
+    // (every weekday is three letters, all as wide in this font)
+    let widest = if complications.weekday { "WED 00" } else { "00" };
+    let size = Dimensions {
+        width: font::text_width(widest, scale) + 2 * padding,
+        height: font::text_height(scale) + 2 * padding,
+    };
+    let watch = layout.watch;
+    let inward = watch.dial_radius as f32 * DATE_OUTER_EDGE - (size.width / 2) as f32;
+    let center = match complications.date_position {
+        DatePosition::Three => layout.center.polar(0.0, inward),
+        DatePosition::Nine => layout.center.polar(PI, inward),
+        DatePosition::Six => {
+            let readout_bottom = watch.readout_y + font::text_height(watch.readout_scale) as isize;
+            Point::new(layout.center.x, readout_bottom + (padding + size.height / 2) as isize)
+        }
+    };
+    let top_left = Point::new(center.x - (size.width / 2) as isize, center.y - (size.height / 2) as isize);
+    canvas.fill_rect(top_left, size, draw::adjust_brightness(palette.background, DATE_WINDOW_SHADE));
+    canvas.draw_rect(top_left, size, watch.dial_thick, palette.foreground);
+
+    let text = if complications.weekday { format!("{} {}", today.format("%a"), today.day()) } else { today.day().to_string() };
+    let origin = Point::new(
+        center.x - (font::text_width(&text, scale) / 2) as isize,
+        center.y - (font::text_height(scale) / 2) as isize,
+    );
+    canvas.draw_text(origin, &text, scale, palette.foreground);
+}
+
+/// A small moon at `angle` from 3 o'clock, lit for `phase` (0.0 new, 0.5 full, see `moon_phase`).
+///
+/// The lit disc is drawn in the foreground, then a background colored disc slides over it from
+/// one side, clipped to the moon: to the left while waxing (lit on the right, as seen from the
+/// northern hemisphere), to the right while waning. How far it slides follows the lit fraction.
+fn draw_moon(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, phase: f64, angle: f32) {
+    let (scale, padding) = box_scale(layout);
+    let radius = font::text_height(scale) / 2 + padding;
+    let center = layout.center.polar(angle, layout.watch.dial_radius as f32 * DISTANCE);
+
+    let lit = (1.0 - (phase * TAU).cos()) / 2.0;
+    let offset = (2.0 * radius as f64 * lit).round() as isize;
+    let shadow = Point::new(if phase < 0.5 { center.x - offset } else { center.x + offset }, center.y);
+    canvas.with_circular_clip(center, radius, |canvas| {
+        canvas.draw_filled_circle(center, radius, palette.foreground);
+        canvas.draw_filled_circle(shadow, radius, palette.background);
+    });
+    // The outline keeps a new moon visible
+    canvas.draw_circle(center, radius as isize, layout.watch.dial_thick as isize, palette.foreground);
+}
+
+/// How far the moon is through its cycle at `now`, 0.0 to 1.0: 0.0 is new, 0.5 full.
+/// Counted in average months from a known new moon, which is within a day or so of the real phase.
+fn moon_phase(now: DateTime<Utc>) -> f64 {
+    let days = (now.timestamp() - REFERENCE_NEW_MOON) as f64 / 86_400.0;
+    (days / SYNODIC_MONTH).rem_euclid(1.0)
+}
@@ -0,0 +1,626 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::scene::SceneKind;
+use crate::theme::{Theme, ThemePalette};
+
+const CONFIG_DIR: &str = "watchrs";
+const CONFIG_FILE: &str = "settings.toml";
+
+/// Settings that survive a restart, stored as TOML in the platform config dir
+/// (e.g. `~/.config/watchrs/settings.toml` on Linux).
+///
+/// All fields are kept whichever feature is built, so running the watch doesn't forget the
+/// metronome tempo and vice versa. Values are not range-checked here, the caller clamps them
+/// to its MIN/MAX constants.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub fps: u32,
+    pub bpm: u32,
+    /// Metronome clicks per beat: 1 for the beat alone, 2 for eighths, 3 for triplets, 4 for sixteenths.
+    pub subdivisions: u32,
+    /// Flash on every metronome beat, brighter on the downbeat, for practicing without sound.
+    pub beat_flash: BeatFlash,
+    /// Silent beats counted down on screen before the metronome starts clicking, 0 for none.
+    pub count_in: u32,
+    /// Degrees the metronome arm swings to either side of vertical, 20 to 90.
+    pub swing_arc: f32,
+    /// Where the metronome arm's pivot is: "bottom" for an arm pointing up, "top" for one hanging from the apex.
+    pub pendulum_pivot: PendulumPivot,
+    /// Metronome redraws per second, 5 to 60, to save power on slow devices. Unset redraws with
+    /// every frame of the monitor. The clicks keep their timing either way.
+    pub render_fps: Option<u32>,
+    /// Countdown timer duration in seconds.
+    pub timer: u32,
+    /// IANA time zone names for the world clock, one small clock each.
+    pub world_zones: Vec<String>,
+    /// Clocks per row of the world clock, unset to keep the grid about as wide as tall.
+    pub world_columns: Option<usize>,
+    /// 12-hour readout with AM/PM. Stays unset (the locale decides) until the user toggles it.
+    pub twelve_hour: Option<bool>,
+    /// Hour labels around the watch dial: "arabic", "roman" or "none" for a minimalist face.
+    pub numerals: Numerals,
+    /// Roman numerals write 4 the way watchmakers do, IIII instead of IV.
+    pub roman_iiii: bool,
+    /// Look of the watch's rectangular frame: "flat", "raised" or "inset".
+    pub bezel: Bezel,
+    /// Round watch: a circular bezel instead of the rectangular frame, with nothing drawn past it.
+    pub round_face: bool,
+    /// Watch hand lengths as fractions of the dial radius.
+    pub hand_lengths: HandLengths,
+    /// Where the clock (or metronome) sits in a window wider or taller than it is.
+    pub dial_position: DialPosition,
+    /// Extras on the watch dial, each on or off.
+    pub complications: Complications,
+    /// Chiming at the top of the hour.
+    pub chime: Chime,
+    /// Pictures to draw the watch dial and hands with instead.
+    pub face_images: FaceImagePaths,
+    /// Fading copies of the seconds hand at its last few positions, 0 for none.
+    pub seconds_trail: usize,
+    /// How the watch's seconds hand moves: "tick" once a second, or "sweep" with the time itself.
+    pub seconds_mode: SecondsMode,
+    /// How the watch shows the seconds: "hand", or "ring" for an arc filling the dial over each minute.
+    pub seconds_display: SecondsDisplay,
+    /// Redraw a ticking watch only when the second changes, woken right at it instead of at the
+    /// FPS setting's pace. A sweeping hand still gets its full frame rate.
+    pub power_saving: bool,
+    /// Treat running on battery like power saving, back to the FPS setting on AC power. Checked
+    /// every 30 seconds, only in builds with the `battery-saver` feature.
+    pub battery_saver: bool,
+    /// Speed readout (FPS or BPM) in the top-left corner.
+    pub show_hud: bool,
+    /// Fade everything into the background for a moment before the window closes.
+    pub fade_on_close: bool,
+    /// Keep the window above other windows, where the platform supports it.
+    pub always_on_top: bool,
+    /// No title bar or borders, for a bare clock. The window moves by dragging it anywhere.
+    pub borderless: bool,
+    /// See-through background, only the clock itself is drawn. Needs a compositor that honors the
+    /// alpha of the window's pixels, which softbuffer only passes on under X11: elsewhere
+    /// (Wayland, macOS, Windows) the background comes out black. Only read at startup.
+    pub transparent: bool,
+    pub theme: Theme,
+    /// Colors that replace the theme's, unset means the theme decides.
+    /// Each scene has its own foreground, so an unset one isn't written back as the other's.
+    pub background: Option<Rgb>,
+    /// Frame, dial and hands. The watch draws the hour/minute hands in darker shades of it.
+    pub foreground: Option<Rgb>,
+    /// Set to fade the background from its color at the top to this one at the bottom.
+    pub background_gradient: Option<Rgb>,
+    /// The watch's seconds hand, unset for the theme's (red, but white with high contrast).
+    pub seconds_hand: Option<Rgb>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fps: 1,
+            bpm: 60,
+            subdivisions: 1,
+            beat_flash: BeatFlash::default(),
+            count_in: 0,
+            swing_arc: 60.0,
+            pendulum_pivot: PendulumPivot::default(),
+            render_fps: None,
+            timer: 5 * 60,
+            world_zones: ["America/Los_Angeles", "America/New_York", "Europe/London", "Europe/Berlin", "Asia/Tokyo", "Australia/Sydney"]
+                .map(String::from)
+                .to_vec(),
+            world_columns: None,
+            twelve_hour: None,
+            numerals: Numerals::default(),
+            roman_iiii: true,
+            bezel: Bezel::default(),
+            round_face: false,
+            hand_lengths: HandLengths::default(),
+            dial_position: DialPosition::default(),
+            complications: Complications::default(),
+            chime: Chime::default(),
+            face_images: FaceImagePaths::default(),
+            seconds_trail: 0,
+            seconds_mode: SecondsMode::default(),
+            seconds_display: SecondsDisplay::default(),
+            power_saving: false,
+            battery_saver: false,
+            show_hud: false,
+            fade_on_close: false,
+            always_on_top: false,
+            borderless: false,
+            transparent: false,
+            theme: Theme::default(),
+            background: None,
+            foreground: None,
+            background_gradient: None,
+            seconds_hand: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Colors to draw `scene` with: the theme's, with the ones set here on top.
+    /// A transparent window keys out the flat background color, so it has no gradient.
+    pub fn palette(&self, scene: SceneKind) -> ThemePalette {
+        let gradient = if self.transparent { None } else { self.background_gradient };
+        self.theme.colors(scene).with_overrides(self.background, self.foreground, gradient, self.seconds_hand)
+    }
+
+    /// Load the settings file, falling back to defaults if it is missing or can't be read.
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Self::default();
+        };
+        Self::load_from(&path)
+    }
+
+    /// The settings in the file at `path`, or the defaults. A file that can't be read or parsed
+    /// is moved aside to `settings.toml.bak` first, so the defaults saved on exit don't take its place
+    /// (one bad color shouldn't cost the rest of the file).
+    fn load_from(path: &Path) -> Self {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            // No file yet (first run), nothing to warn about
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                set_aside(path, &e.to_string());
+                return Self::default();
+            }
+        };
+        toml::from_str(&text).unwrap_or_else(|e| {
+            set_aside(path, e.message());
+            Self::default()
+        })
+    }
+
+    /// Read the settings file again while running, for the hot reload.
+    /// `None` if it is gone or doesn't parse (with a warning), so the settings in use stay.
+    #[cfg(feature = "hot-reload")]
+    pub fn reload() -> Option<Self> {
+        let path = config_path()?;
+        let text = fs::read_to_string(&path).ok()?;
+        toml::from_str(&text)
+            .map_err(|e| warn!("Keeping the current settings, '{}' doesn't parse: {e}", path.display()))
+            .ok()
+    }
+
+    /// Write the settings file, creating the config directory if needed.
+    pub fn save(&self) {
+        let Some(path) = config_path() else {
+            return;
+        };
+        self.save_to(&path);
+    }
+
+    fn save_to(&self, path: &Path) {
+        let result = toml::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                fs::write(path, text).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("Could not save settings to '{}': {e}", path.display());
+        }
+    }
+}
+
+/// Move the settings file at `path` that couldn't be loaded (for `problem`) out of the way of the next save.
+fn set_aside(path: &Path, problem: &str) {
+    let backup = path.with_extension("toml.bak");
+    match fs::rename(path, &backup) {
+        Ok(()) => warn!("Ignoring corrupt settings file '{}', kept as '{}': {}", path.display(), backup.display(), problem.trim_end()),
+        Err(e) => warn!("Ignoring corrupt settings file '{}' ({}), and it couldn't be kept aside: {e}", path.display(), problem.trim_end()),
+    }
+}
+
+/// Extras on the watch dial besides the time, a `[complications]` table in the settings file.
+/// All off unless turned on there or with their keys.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Complications {
+    /// Day of the month in a box, a date window, at 3 o'clock unless `date_position` says otherwise (D).
+    pub date: bool,
+    /// The day of the week in the date window too, "WED 12".
+    pub weekday: bool,
+    /// Where the date window is.
+    pub date_position: DatePosition,
+    /// Phase of the moon at 9 o'clock, or at 3 o'clock with the date window at 9 (M).
+    pub moon_phase: bool,
+}
+
+/// Where on the watch dial the date window is.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DatePosition {
+    /// 3 o'clock, where most watches have it.
+    #[default]
+    Three,
+    /// 6 o'clock, under the digital readout.
+    Six,
+    /// 9 o'clock.
+    Nine,
+}
+
+/// The watch's chime on the hour, a `[chime]` table in the settings file. Off unless turned on there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Chime {
+    /// Chime at the top of every hour.
+    pub hourly: bool,
+    /// At half past too, a shorter one.
+    pub half_hour: bool,
+    /// "westminster" for the Big Ben quarters with the hour struck after them, "single" for one tone.
+    pub style: ChimeStyle,
+    /// Play it, needs a build with the `audio` feature.
+    pub sound: bool,
+    /// Show the time over the face for a few seconds as it chimes, seen with the sound off too.
+    pub visual: bool,
+}
+
+impl Default for Chime {
+    fn default() -> Self {
+        Self { hourly: false, half_hour: false, style: ChimeStyle::default(), sound: true, visual: false }
+    }
+}
+
+/// What the chime plays.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChimeStyle {
+    /// One bell tone.
+    #[default]
+    Single,
+    /// The Westminster quarters, with one low strike per hour after them on the hour.
+    Westminster,
+}
+
+#[cfg(feature = "watch")]
+impl Complications {
+    pub fn any(self) -> bool {
+        self.date || self.moon_phase
+    }
+}
+
+/// Pictures for a custom watch face, a `[face_images]` table in the settings file (see images.rs).
+/// Paths are relative to the settings file's folder unless absolute, unset parts are drawn as usual.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaceImagePaths {
+    pub dial: Option<PathBuf>,
+    pub hour_hand: Option<PathBuf>,
+    pub minute_hand: Option<PathBuf>,
+    pub seconds_hand: Option<PathBuf>,
+}
+
+/// Length of each watch hand as a fraction of the dial radius, 0.0 to 1.0 (reaching the dial).
+/// A `[hand_lengths]` table in the settings file, any hand left out keeps its default.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandLengths {
+    pub hour: f32,
+    pub minute: f32,
+    pub seconds: f32,
+}
+
+impl Default for HandLengths {
+    fn default() -> Self {
+        // Hour hand is the shortest, the seconds hand reaches the dial
+        Self { hour: 0.5, minute: 0.75, seconds: 1.0 }
+    }
+}
+
+impl HandLengths {
+    /// These lengths with any outside 0.0..=1.0 clamped into it, and reported.
+    pub fn clamped(self) -> Self {
+        let clamp = |name: &str, ratio: f32, default: f32| {
+            if (0.0..=1.0).contains(&ratio) {
+                return ratio;
+            }
+            // NaN has no side to clamp to, it gets the default
+            let clamped = if ratio.is_nan() { default } else { ratio.clamp(0.0, 1.0) };
+            warn!("{name} hand length {ratio} is out of range (0.0..=1.0), using {clamped}");
+            clamped
+        };
+        let default = Self::default();
+        Self {
+            hour: clamp("Hour", self.hour, default.hour),
+            minute: clamp("Minute", self.minute, default.minute),
+            seconds: clamp("Seconds", self.seconds, default.seconds),
+        }
+    }
+}
+
+/// Where the square the clock is drawn in goes along the longer side of the window.
+/// The frame stays around the whole window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DialPosition {
+    /// Left, or top in a window taller than wide.
+    Start,
+    /// In the middle, with the same room on both sides.
+    #[default]
+    Center,
+    /// Right, or bottom.
+    End,
+}
+
+/// Where the metronome arm swings from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PendulumPivot {
+    /// In the middle of the housing, the arm points up and swings over the apex.
+    #[default]
+    Bottom,
+    /// At the apex, the arm hangs down and swings under it, like a real metronome's pendulum.
+    Top,
+}
+
+/// How the watch frame is drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Bezel {
+    /// One plain color.
+    #[default]
+    Flat,
+    /// Lighter top and left, darker bottom and right, like it stands out of the window.
+    Raised,
+    /// Shaded the other way round, sunk into the window.
+    Inset,
+}
+
+/// What shows the seconds on the watch.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecondsDisplay {
+    /// A thin hand over the others.
+    #[default]
+    Hand,
+    /// An arc along the dial from 12 o'clock, empty at the top of the minute and full circle at its end.
+    Ring,
+}
+
+/// How the watch's seconds hand moves, switched with the S key.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecondsMode {
+    /// Continuous motion, like a mechanical sweep movement.
+    Sweep,
+    /// One crisp jump per second, like a quartz watch.
+    #[default]
+    Tick,
+}
+
+impl SecondsMode {
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn toggled(self) -> Self {
+        match self {
+            SecondsMode::Sweep => SecondsMode::Tick,
+            SecondsMode::Tick => SecondsMode::Sweep,
+        }
+    }
+
+    /// Position of the seconds hand, in seconds (0..60), for the current time.
+    ///
+    /// `seconds` is read from the wall clock at each redraw, fraction included, so a sweeping hand
+    /// is always exactly where the time is when the frame is drawn. It never lags a frame behind
+    /// or runs ahead of it, whatever the frame rate. There is no tick count to interpolate between.
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn hand_seconds(self, seconds: f32) -> f32 {
+        match self {
+            SecondsMode::Sweep => seconds,
+            SecondsMode::Tick => seconds.floor(),
+        }
+    }
+}
+
+/// What marks the hours around the watch dial, cycled with the N key.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Numerals {
+    /// 1 to 12.
+    #[default]
+    Arabic,
+    /// I to XII.
+    Roman,
+    /// Only the ticks.
+    None,
+}
+
+impl Numerals {
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn next(self) -> Self {
+        match self {
+            Numerals::Arabic => Numerals::Roman,
+            Numerals::Roman => Numerals::None,
+            Numerals::None => Numerals::Arabic,
+        }
+    }
+}
+
+/// What lights up on a metronome beat, cycled with the V key.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BeatFlash {
+    /// No flash, the pendulum and the click keep time.
+    #[default]
+    Off,
+    /// The frame brightens towards white.
+    Frame,
+    /// The frame and, more faintly, the background.
+    Background,
+}
+
+impl BeatFlash {
+    #[cfg_attr(not(feature = "metronome"), allow(dead_code))]
+    pub fn next(self) -> Self {
+        match self {
+            BeatFlash::Off => BeatFlash::Frame,
+            BeatFlash::Frame => BeatFlash::Background,
+            BeatFlash::Background => BeatFlash::Off,
+        }
+    }
+}
+
+/// A color as stored in the settings file.
+///
+/// Written as a hex string (`"#4b5f64"`), an `[r, g, b]` array with channels 0-255 is read too.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RgbValue", into = "String")]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parse `#rrggbb` (the `#` is optional), e.g. `#4b5f64`.
+    pub fn from_hex(text: &str) -> Result<Self, String> {
+        let hex = text.trim().strip_prefix('#').unwrap_or(text.trim());
+        // Checked up front, from_str_radix alone would also accept a sign like "+f"
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid color '{text}', expected a hex color like #4b5f64"));
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        Ok(Self::new(channel(0), channel(2), channel(4)))
+    }
+
+    /// As a pixel for the canvas.
+    pub const fn to_pixel(self) -> u32 {
+        watchrs::draw::color_rgb(self.r, self.g, self.b)
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(color: Rgb) -> Self {
+        color.to_string()
+    }
+}
+
+/// The forms a color may take in the settings file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RgbValue {
+    Hex(String),
+    Channels([u32; 3]),
+}
+
+impl TryFrom<RgbValue> for Rgb {
+    type Error = String;
+
+    fn try_from(value: RgbValue) -> Result<Self, Self::Error> {
+        match value {
+            RgbValue::Hex(text) => Rgb::from_hex(&text),
+            RgbValue::Channels(channels) => {
+                // Wider than u8 on purpose, so 300 gets this message instead of a generic type error
+                let [r, g, b] = channels.map(u8::try_from);
+                match (r, g, b) {
+                    (Ok(r), Ok(g), Ok(b)) => Ok(Rgb::new(r, g, b)),
+                    _ => Err(format!("invalid color {channels:?}, every channel must be 0-255")),
+                }
+            }
+        }
+    }
+}
+
+/// Where the settings file lives, `None` if the platform has no config dir.
+pub fn config_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join(CONFIG_DIR).join(CONFIG_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex_reads_rrggbb_with_or_without_the_hash() {
+        assert_eq!(Rgb::from_hex("#4b5f64"), Ok(Rgb::new(0x4b, 0x5f, 0x64)));
+        assert_eq!(Rgb::from_hex("4B5F64"), Ok(Rgb::new(0x4b, 0x5f, 0x64)));
+        assert_eq!(Rgb::from_hex("  #ffffff "), Ok(Rgb::new(255, 255, 255)));
+        assert_eq!(Rgb::from_hex("#000000"), Ok(Rgb::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn from_hex_refuses_malformed_colors() {
+        for text in ["", "#", "#4b5f6", "#4b5f644", "#4g5f64", "#+f5f64", "##4b5f64", "#4b 5f6", "#fff"] {
+            assert!(Rgb::from_hex(text).is_err(), "'{text}' was taken for a color");
+        }
+    }
+
+    #[test]
+    fn colors_in_the_settings_file_are_hex_strings_or_channel_arrays() {
+        let settings: Settings = toml::from_str("background = \"#102030\"\nforeground = [1, 2, 3]").unwrap();
+        assert_eq!(settings.background, Some(Rgb::new(0x10, 0x20, 0x30)));
+        assert_eq!(settings.foreground, Some(Rgb::new(1, 2, 3)));
+        // Written back as hex
+        assert!(toml::to_string(&settings).unwrap().contains("background = \"#102030\""));
+
+        for bad in ["background = \"#10203\"", "background = [256, 0, 0]", "background = [1, 2]"] {
+            assert!(toml::from_str::<Settings>(bad).is_err(), "'{bad}' was taken");
+        }
+    }
+
+    #[test]
+    fn a_sweeping_hand_keeps_the_fraction_of_a_second() {
+        assert_eq!(SecondsMode::Sweep.hand_seconds(12.5), 12.5);
+        assert_eq!(SecondsMode::Tick.hand_seconds(12.5), 12.0);
+        assert_eq!(SecondsMode::Tick.hand_seconds(59.99), 59.0);
+        assert_eq!(SecondsMode::Tick.toggled(), SecondsMode::Sweep);
+
+        let settings: Settings = toml::from_str("seconds_mode = \"sweep\"").unwrap();
+        assert_eq!(settings.seconds_mode, SecondsMode::Sweep);
+        assert_eq!(Settings::default().seconds_mode, SecondsMode::Tick);
+    }
+
+    /// A directory of its own for `test` under the system's temp dir, empty.
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("watchrs-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn a_corrupt_settings_file_is_kept_instead_of_saved_over() {
+        let dir = scratch_dir("corrupt-settings");
+        let path = dir.join(CONFIG_FILE);
+        let text = "bpm = 96\nbackground = \"#10203\"\n";
+        fs::write(&path, text).unwrap();
+
+        let settings = Settings::load_from(&path);
+        assert_eq!(settings.bpm, Settings::default().bpm);
+        // Saving on exit writes a new file, the old one is still there to fix
+        settings.save_to(&path);
+        assert_eq!(fs::read_to_string(dir.join("settings.toml.bak")).unwrap(), text);
+        assert!(toml::from_str::<Settings>(&fs::read_to_string(&path).unwrap()).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_good_or_missing_settings_file_is_left_alone() {
+        let dir = scratch_dir("good-settings");
+        let path = dir.join(CONFIG_FILE);
+        assert_eq!(Settings::load_from(&path).bpm, Settings::default().bpm);
+        fs::write(&path, "bpm = 96\n").unwrap();
+        assert_eq!(Settings::load_from(&path).bpm, 96);
+        assert!(path.exists() && !dir.join("settings.toml.bak").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+// On-screen buttons for the mouse: slower, faster and pause, in the bottom-right corner inside
+// the frame. They do what the -, + and pause keys do, for whoever has no keyboard at hand.
+//
+// They show up when the cursor moves over the window and hide again CONTROLS_TIMEOUT after it
+// stops or right away when it leaves (see main.rs), so they're out of the way on a desk clock.
+// Drawn as part of the hud, after everything else.
+
+use std::time::Duration;
+
+use watchrs::draw::{self, Canvas, Dimensions, Point, Rect};
+use watchrs::font;
+use crate::layout::Layout;
+use crate::theme::ThemePalette;
+
+/// How long the buttons stay after the cursor stops moving.
+pub const CONTROLS_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Button background, as a share of the foreground over the background. Brighter under the cursor.
+const BUTTON_SHADE: f32 = 0.15;
+const HOVER_SHADE: f32 = 0.35;
+
+/// What a button does, the same as its key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Control {
+    Slower,
+    Faster,
+    Pause,
+}
+
+// Left to right
+const CONTROLS: [(Control, &str); 3] = [
+    (Control::Slower, "-"),
+    (Control::Faster, "+"),
+    (Control::Pause, "II"),
+];
+
+/// One square button with a text label.
+pub struct Button {
+    pub rect:   Rect,
+    pub label:  &'static str,
+}
+
+impl Button {
+    pub fn contains(&self, point: Point) -> bool {
+        let (x, y) = (point.x as usize, point.y as usize);
+        point.x >= 0 && point.y >= 0 && (self.rect.x0..self.rect.x1).contains(&x) && (self.rect.y0..self.rect.y1).contains(&y)
+    }
+}
+
+/// Every button with what it does, placed for `layout`.
+/// Sized after the speed readout's text, so they grow with the window like it does.
+pub fn buttons(layout: &Layout) -> [(Control, Button); 3] {
+    let side = font::text_height(layout.hud_scale) * 2;
+    let gap = side / 4;
+    let inset = layout.frame_padding + layout.thick * 2;
+    let top = layout.height.saturating_sub(inset + side);
+    let left = layout.width.saturating_sub(inset + CONTROLS.len() * side + (CONTROLS.len() - 1) * gap);
+    std::array::from_fn(|i| {
+        let (control, label) = CONTROLS[i];
+        let x0 = left + i * (side + gap);
+        (control, Button { rect: Rect { x0, y0: top, x1: x0 + side, y1: top + side }, label })
+    })
+}
+
+/// The control whose button is at `point`, if any.
+pub fn hit(layout: &Layout, point: Point) -> Option<Control> {
+    buttons(layout).into_iter().find(|(_, button)| button.contains(point)).map(|(control, _)| control)
+}
+
+/// Draw the buttons, the one for `hovered` highlighted, and return the boxes they cover.
+pub fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, hovered: Option<Control>) -> Vec<Rect> {
+    buttons(layout)
+        .into_iter()
+        .map(|(control, button)| {
+            let shade = if hovered == Some(control) { HOVER_SHADE } else { BUTTON_SHADE };
+            let Rect { x0, y0, .. } = button.rect;
+            let (width, height) = (button.rect.width(), button.rect.height());
+            canvas.fill_rect(Point::new(x0 as isize, y0 as isize), Dimensions { width, height }, draw::blend(palette.background, palette.foreground, shade));
+
+            // Label in the middle
+            let scale = layout.hud_scale;
+            let origin = Point::new(
+                (x0 + width.saturating_sub(font::text_width(button.label, scale)) / 2) as isize,
+                (y0 + height.saturating_sub(font::text_height(scale)) / 2) as isize,
+            );
+            canvas.draw_text(origin, button.label, scale, palette.foreground);
+            button.rect
+        })
+        .collect()
+}
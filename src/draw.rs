@@ -1,3 +1,4 @@
+use crate::font;
 
 /// Pack 8-bit R, G, B into a single u32 pixel in softbuffer's format: 0x00RRGGBB.
 ///
@@ -20,6 +21,99 @@ pub const fn color_rgb(r: u8, g: u8, b: u8) -> u32 {
     red_bits | green_bits | blue_bits
 }
 
+/// Pack 8-bit R, G, B and an alpha into a single u32: 0xAARRGGBB.
+///
+/// Same layout as `color_rgb`, but the top byte (31..24) carries the alpha:
+/// 255 is fully opaque, 0 is fully transparent. Only `blend_pixel` looks at it,
+/// `put_pixel` and softbuffer ignore it.
+#[inline]
+pub const fn color_rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    ((a as u32) << 24) | color_rgb(r, g, b)
+}
+
+/// Apply `f` to the R, G and B channels of `color` (each 0.0-255.0) and pack the result again,
+/// every channel rounded and clamped to 0-255. The result is an opaque 0x00RRGGBB pixel.
+///
+/// The building block of the color helpers below, e.g. `with_channels(c, |r, g, b| (g, r, b))`
+/// swaps red and green.
+pub fn with_channels(color: u32, f: impl FnOnce(f32, f32, f32) -> (f32, f32, f32)) -> u32 {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32;
+    let (r, g, b) = f(channel(16), channel(8), channel(0));
+    // `as u8` saturates, but NaN would become 0 silently, clamp first to make the intent clear
+    let pack = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+    color_rgb(pack(r), pack(g), pack(b))
+}
+
+/// `color` with every channel multiplied by `factor`: below 1.0 darkens, above brightens
+/// (channels stop at 255, so very bright colors wash out towards white).
+pub fn adjust_brightness(color: u32, factor: f32) -> u32 {
+    with_channels(color, |r, g, b| (r * factor, g * factor, b * factor))
+}
+
+/// Darker shade of `color`, every channel scaled by `brightness` (0.0 = black, 1.0 = unchanged).
+pub fn shade(color: u32, brightness: f32) -> u32 {
+    adjust_brightness(color, brightness.clamp(0.0, 1.0))
+}
+
+/// `color` with `delta` added to every channel, clamped to 0-255 (a negative `delta` darkens).
+fn offset_channels(color: u32, delta: i32) -> u32 {
+    let delta = delta as f32;
+    with_channels(color, |r, g, b| (r + delta, g + delta, b + delta))
+}
+
+/// The color `t` of the way from `a` to `b`, channel by channel (0.0 = `a`, 1.0 = `b`).
+/// `t` is clamped to 0.0..=1.0. The result is an opaque 0x00RRGGBB pixel.
+pub fn lerp_color(a: u32, b: u32, t: f32) -> u32 {
+    let t = t.clamp(0.0, 1.0);
+    let channel = |color: u32, shift: u32| ((color >> shift) & 0xFF) as f32;
+    // Interpolate each channel of `a` towards the same one of `b`
+    with_channels(a, |r, g, bl| {
+        (
+            r + (channel(b, 16) - r) * t,
+            g + (channel(b, 8) - g) * t,
+            bl + (channel(b, 0) - bl) * t,
+        )
+    })
+}
+
+/// Mix `src` over `dst` by `coverage` (0.0 = keep dst, 1.0 = replace with src), channel by channel.
+/// The result is an opaque 0x00RRGGBB pixel.
+pub fn blend(dst: u32, src: u32, coverage: f32) -> u32 {
+    lerp_color(dst, src, coverage)
+}
+
+/// The 0xAARRGGBB color at (`x`, `y`) in `src`, between the centers of the four pixels around it,
+/// where pixel (i, j) has its center at (i, j). Past the edges the edge pixels carry on.
+///
+/// With `alpha` the colors are weighted by their alpha too, so a transparent pixel's color doesn't
+/// bleed into an opaque neighbour (a clear black next to red would darken the edge otherwise).
+/// Without it the top byte is ignored and every pixel counts as opaque.
+fn sample_bilinear(src: &[u32], dims: Dimensions, x: f32, y: f32, alpha: bool) -> u32 {
+    let x = x.clamp(0.0, (dims.width - 1) as f32);
+    let y = y.clamp(0.0, (dims.height - 1) as f32);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(dims.width - 1), (y0 + 1).min(dims.height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let corners = [
+        (src[y0 * dims.width + x0], (1.0 - fx) * (1.0 - fy)),
+        (src[y0 * dims.width + x1], fx * (1.0 - fy)),
+        (src[y1 * dims.width + x0], (1.0 - fx) * fy),
+        (src[y1 * dims.width + x1], fx * fy),
+    ];
+    let channel = |color: u32, shift: u32| ((color >> shift) & 0xFF) as f32;
+    let opacity = |color: u32| if alpha { channel(color, 24) } else { 255.0 };
+    let total: f32 = corners.iter().map(|&(color, weight)| opacity(color) * weight).sum();
+    if total <= 0.0 {
+        return 0;
+    }
+    let mix = |shift: u32| {
+        let sum: f32 = corners.iter().map(|&(color, weight)| channel(color, shift) * opacity(color) * weight).sum();
+        (sum / total).round().clamp(0.0, 255.0) as u32
+    };
+    ((total.round() as u32) << 24) | (mix(16) << 16) | (mix(8) << 8) | mix(0)
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Point {
     pub x: isize,
@@ -30,24 +124,111 @@ impl Point {
     pub fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
-}
 
+    /// The point `length` pixels away from `self` in the direction of `angle` (radians).
+    /// Screen y grows downwards, so 0 is 3 o'clock and -PI/2 is 12 o'clock.
+    pub fn polar(self, angle: f32, length: f32) -> Point {
+        Point::new(
+            self.x + (angle.cos() * length).round() as isize,
+            self.y + (angle.sin() * length).round() as isize,
+        )
+    }
+}
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Dimensions {
     pub width: usize,
     pub height: usize,
 }
 
+/// Axis-aligned block of pixels, `x0..x1` by `y0..y1` (end exclusive).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Rect {
+    /// Smallest rect holding all `points` grown by `pad` on every side, clipped to `width` x `height`.
+    pub fn around(points: &[Point], pad: usize, width: usize, height: usize) -> Rect {
+        let pad = pad as isize;
+        let min_x = points.iter().map(|p| p.x).min().unwrap_or(0) - pad;
+        let max_x = points.iter().map(|p| p.x).max().unwrap_or(0) + pad;
+        let min_y = points.iter().map(|p| p.y).min().unwrap_or(0) - pad;
+        let max_y = points.iter().map(|p| p.y).max().unwrap_or(0) + pad;
+
+        // Clip to the canvas, the +1 makes the max pixel itself part of the rect
+        let clip = |v: isize, max: usize| v.clamp(0, max as isize) as usize;
+        Rect {
+            x0: clip(min_x, width),
+            y0: clip(min_y, height),
+            x1: clip(max_x + 1, width),
+            y1: clip(max_y + 1, height),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.x1.saturating_sub(self.x0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1.saturating_sub(self.y0)
+    }
+}
+
+/// How the ends of a thick line look.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// Half a disc past each end point, what stamping circles along the line gives.
+    Round,
+    /// Cut square across at the end points, the line is a rectangle exactly `thickness` wide.
+    Flat,
+}
+
+/// How `Canvas::blit_scaled` picks a color for a pixel that falls between source pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    /// The source pixel it lands on: blocky when scaled up, but sharp and exact.
+    Nearest,
+    /// The four around it, weighted by distance: smooth, a little soft.
+    Bilinear,
+}
+
+/// A picture for `Canvas::blit_rotated`, row by row, each pixel 0xAARRGGBB like `color_rgba`.
+pub struct Image {
+    pub width:  usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+    pub pivot:  (f32, f32),     // the spot that goes on the point it's drawn at, in pixels from the top-left
+}
+
+/// The buffer handed to `Canvas::new` doesn't hold exactly `width * height` pixels.
+#[derive(Debug)]
+pub struct SizeMismatch {
+    pub expected: usize,
+    pub actual: usize,
+}
+
 pub struct Canvas<'a> {
     pub buf: &'a mut [u32],
     pub size: Dimensions,
+    clip: Option<(Point, usize)>,   // center and radius of the only disc drawn into, see with_circular_clip
 }
 
 impl<'a> Canvas<'a> {
-    pub fn new(buf: &'a mut [u32], size: Dimensions) -> Self {
-        // (Optional) sanity check in debug builds:
-        debug_assert_eq!(buf.len(), (size.width) * (size.height));
-        Self { buf, size }
+    /// Wrap a pixel buffer of `size.width * size.height` pixels.
+    ///
+    /// During fast resizes the window size and the surface buffer can briefly disagree.
+    /// Indexing such a buffer would go out of bounds, so a mismatch is refused with an error
+    /// instead, and the caller is expected to skip that frame.
+    pub fn new(buf: &'a mut [u32], size: Dimensions) -> Result<Self, SizeMismatch> {
+        let expected = size.width * size.height;
+        if buf.len() != expected {
+            return Err(SizeMismatch { expected, actual: buf.len() });
+        }
+        Ok(Self { buf, size, clip: None })
     }
 
     pub fn width(&self) -> usize  { 
@@ -74,7 +255,7 @@ impl<'a> Canvas<'a> {
     //     self.width() as f32 / self.height() as f32
     // }
 
-    /// Convert normalized coords (0..1) into pixel coordinates.
+    // Convert normalized coords (0..1) into pixel coordinates.
     // pub fn _from_norm(&self, x: f32, y: f32) -> Point {
     //     let px = (x.clamp(0.0, 1.0) * (self.width().saturating_sub(1) as f32)).round() as isize;
     //     let py = (y.clamp(0.0, 1.0) * (self.height().saturating_sub(1) as f32)).round() as isize;
@@ -86,30 +267,316 @@ impl<'a> Canvas<'a> {
         self.buf.fill(color);
     }
 
-    /// Plot one pixel at (x,y), ignoring if out of bounds.
-    pub fn put_pixel(&mut self, x: isize, y: isize, color: u32) {
-        if x < 0 || y < 0 {
+    /// Give every pixel an alpha for a transparent window: pixels of the `key` color become fully
+    /// transparent (0x00000000), every other one fully opaque (alpha 255).
+    ///
+    /// Only ever 0 or 255, so it reads the same whether the compositor expects premultiplied alpha or not.
+    /// Edges blended into the background keep a fringe of the `key` color.
+    pub fn key_out(&mut self, key: u32) {
+        for pixel in self.buf.iter_mut() {
+            *pixel = if *pixel == key { 0 } else { *pixel | 0xFF00_0000 };
+        }
+    }
+
+    /// Fill the whole canvas with a gradient from `top` on the first row to `bottom` on the last.
+    /// Each row is one color, mixed channel by channel and rounded like `blend` does.
+    pub fn fill_vertical_gradient(&mut self, top: u32, bottom: u32) {
+        let (width, height) = (self.width(), self.height());
+        if width == 0 {
             return;
         }
+        let last = height.saturating_sub(1).max(1) as f32;
+        for (y, row) in self.buf.chunks_exact_mut(width).enumerate() {
+            row.fill(blend(top, bottom, y as f32 / last));
+        }
+    }
+
+    /// Fill the whole canvas with a gradient from `left` in the first column to `right` in the last.
+    pub fn fill_horizontal_gradient(&mut self, left: u32, right: u32) {
+        let width = self.width();
+        if width == 0 {
+            return;
+        }
+        let last = width.saturating_sub(1).max(1) as f32;
+        // Every row is the same, work it out once and copy it down
+        let row: Vec<u32> = (0..width).map(|x| blend(left, right, x as f32 / last)).collect();
+        for dst in self.buf.chunks_exact_mut(width) {
+            dst.copy_from_slice(&row);
+        }
+    }
+
+    /// Run `draw` with everything it plots kept inside the disc of `radius` around `center`,
+    /// e.g. to keep a round watch face from spilling past its bezel. Returns what `draw` returns.
+    ///
+    /// The clip applies to pixel plotting and `fill_rect`, so every shape and text, but not to the
+    /// whole-canvas fills and copies (`clear`, the gradients, `copy_rect_from`).
+    /// An outer clip is put back afterwards, clips don't intersect.
+    pub fn with_circular_clip<R>(&mut self, center: Point, radius: usize, draw: impl FnOnce(&mut Self) -> R) -> R {
+        let outer = self.clip.replace((center, radius));
+        let result = draw(self);
+        self.clip = outer;
+        result
+    }
+
+    /// Fill a solid rectangle of `size` with its top-left corner at `top_left`.
+    /// Parts outside the canvas are clipped away (a negative origin just starts at the edge),
+    /// the rest is filled one scanline slice at a time.
+    pub fn fill_rect(&mut self, top_left: Point, size: Dimensions, color: u32) {
+        let clip = |v: isize, max: usize| v.clamp(0, max as isize) as usize;
+        let x0 = clip(top_left.x, self.width());
+        let y0 = clip(top_left.y, self.height());
+        let x1 = clip(top_left.x.saturating_add(size.width as isize), self.width());
+        let y1 = clip(top_left.y.saturating_add(size.height as isize), self.height());
+        if x0 >= x1 {
+            return;
+        }
+        for y in y0..y1 {
+            // With a circular clip, only the part of the row inside the disc
+            let (x0, x1) = match self.clip {
+                Some((center, radius)) => {
+                    let (r, dy) = (radius as isize, y as isize - center.y);
+                    if dy.abs() > r {
+                        continue;
+                    }
+                    let half = ((r * r - dy * dy) as f32).sqrt() as isize;
+                    (x0.max(clip(center.x - half, self.width())), x1.min(clip(center.x + half + 1, self.width())))
+                }
+                None => (x0, x1),
+            };
+            if x0 >= x1 {
+                continue;
+            }
+            let row = y * self.width();
+            self.buf[row + x0..row + x1].fill(color);
+        }
+    }
+
+    /// Mix `color` into every pixel of a rectangle of `size` at `top_left` by `coverage`
+    /// (see `blend`), so what was drawn there still shows through. Clipped like `fill_rect`.
+    pub fn blend_rect(&mut self, top_left: Point, size: Dimensions, color: u32, coverage: f32) {
+        let (x1, y1) = (top_left.x.saturating_add(size.width as isize), top_left.y.saturating_add(size.height as isize));
+        for y in top_left.y.max(0)..y1.min(self.height() as isize) {
+            for x in top_left.x.max(0)..x1.min(self.width() as isize) {
+                self.blend_coverage(x, y, color, coverage);
+            }
+        }
+    }
+
+    /// Copy the pixels inside `rect` from `src`, a buffer of the same size as the canvas.
+    /// Used to restore a region from a cached background without redrawing it.
+    pub fn copy_rect_from(&mut self, src: &[u32], rect: Rect) {
+        debug_assert_eq!(src.len(), self.buf.len());
+        let x1 = rect.x1.min(self.width());
+        let y1 = rect.y1.min(self.height());
+        if rect.x0 >= x1 {
+            return;
+        }
+        for y in rect.y0..y1 {
+            let row = y * self.width();
+            self.buf[row + rect.x0..row + x1].copy_from_slice(&src[row + rect.x0..row + x1]);
+        }
+    }
+
+    /// Draw `image` scaled by `scale` with its pivot on `center`, turned so that its top points
+    /// along `angle` (as in `Point::polar`, -PI/2 leaves it upright). Blended by the image's alpha.
+    ///
+    /// Every canvas pixel in the bounding box of the turned image is mapped back into the image and
+    /// takes the source pixel it lands on, so no pixel is left out at any angle. Returns that box.
+    pub fn blit_rotated(&mut self, image: &Image, center: Point, angle: f32, scale: f32) -> Rect {
+        let empty = Rect { x0: 0, y0: 0, x1: 0, y1: 0 };
+        if image.width == 0 || image.height == 0 || scale <= 0.0 {
+            return empty;
+        }
+        let (sin, cos) = (angle + std::f32::consts::FRAC_PI_2).sin_cos();
+        let (pivot_x, pivot_y) = image.pivot;
+        let (width, height) = (image.width as f32, image.height as f32);
+
+        // The image corners around the pivot, scaled and turned onto the canvas
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)].map(|(x, y)| {
+            let (dx, dy) = ((x - pivot_x) * scale, (y - pivot_y) * scale);
+            Point::new(center.x + (dx * cos - dy * sin).round() as isize, center.y + (dx * sin + dy * cos).round() as isize)
+        });
+        let rect = Rect::around(&corners, 1, self.width(), self.height());
+
+        for y in rect.y0..rect.y1 {
+            for x in rect.x0..rect.x1 {
+                // Back into the image: turn the other way and undo the scale
+                let (dx, dy) = ((x as isize - center.x) as f32, (y as isize - center.y) as f32);
+                let source_x = (dx * cos + dy * sin) / scale + pivot_x;
+                let source_y = (dy * cos - dx * sin) / scale + pivot_y;
+                if source_x < 0.0 || source_y < 0.0 || source_x >= width || source_y >= height {
+                    continue;
+                }
+                let pixel = image.pixels[source_y as usize * image.width + source_x as usize];
+                self.blend_pixel(x as isize, y as isize, pixel);
+            }
+        }
+        rect
+    }
+
+    /// Copy `src`, a picture of `src_dims` pixels row by row, with its top-left corner at `dest`.
+    /// Whatever falls off the canvas is left out. With `blend` each pixel is mixed in by the
+    /// alpha in its top byte (0xAARRGGBB like `color_rgba`), otherwise it replaces the canvas
+    /// pixel and the top byte is dropped. Returns the block of the canvas drawn into.
+    pub fn blit(&mut self, src: &[u32], src_dims: Dimensions, dest: Point, blend: bool) -> Rect {
+        self.blit_scaled(src, src_dims, dest, src_dims, Sampling::Nearest, blend)
+    }
+
+    /// Like `blit`, but stretched to `dest_size`, each canvas pixel sampled from the source by `sampling`.
+    pub fn blit_scaled(&mut self, src: &[u32], src_dims: Dimensions, dest: Point, dest_size: Dimensions, sampling: Sampling, blend: bool) -> Rect {
+        debug_assert_eq!(src.len(), src_dims.width * src_dims.height);
+        let empty = Rect { x0: 0, y0: 0, x1: 0, y1: 0 };
+        if src_dims.width == 0 || src_dims.height == 0 || dest_size.width == 0 || dest_size.height == 0 {
+            return empty;
+        }
+
+        // The part of the destination on the canvas
+        let clip = |v: isize, max: usize| v.clamp(0, max as isize) as usize;
+        let rect = Rect {
+            x0: clip(dest.x, self.width()),
+            y0: clip(dest.y, self.height()),
+            x1: clip(dest.x.saturating_add(dest_size.width as isize), self.width()),
+            y1: clip(dest.y.saturating_add(dest_size.height as isize), self.height()),
+        };
+        if rect.width() == 0 || rect.height() == 0 {
+            return empty;
+        }
+
+        // Source pixels per canvas pixel along each axis
+        let step_x = src_dims.width as f32 / dest_size.width as f32;
+        let step_y = src_dims.height as f32 / dest_size.height as f32;
+        for y in rect.y0..rect.y1 {
+            // Where the middle of this canvas pixel falls in the source
+            let source_y = ((y as isize - dest.y) as f32 + 0.5) * step_y;
+            for x in rect.x0..rect.x1 {
+                let source_x = ((x as isize - dest.x) as f32 + 0.5) * step_x;
+                let pixel = match sampling {
+                    Sampling::Nearest => {
+                        let (sx, sy) = ((source_x as usize).min(src_dims.width - 1), (source_y as usize).min(src_dims.height - 1));
+                        src[sy * src_dims.width + sx]
+                    }
+                    Sampling::Bilinear => sample_bilinear(src, src_dims, source_x - 0.5, source_y - 0.5, blend),
+                };
+                if blend {
+                    self.blend_pixel(x as isize, y as isize, pixel);
+                } else {
+                    self.put_pixel(x as isize, y as isize, pixel & 0x00FF_FFFF);
+                }
+            }
+        }
+        rect
+    }
+
+    /// Index into `buf` of the pixel at (x,y), `None` if it is out of bounds.
+    fn index(&self, x: isize, y: isize) -> Option<usize> {
+        if x < 0 || y < 0 {
+            return None;
+        }
         let (x, y) = (x as usize, y as usize);
         if x  >= self.width() || y >= self.height()  {
+            return None;
+        }
+        Some(y * self.width() + x)
+    }
+
+    /// Like `index`, but also `None` outside the circular clip: the pixels drawing may change.
+    fn draw_index(&self, x: isize, y: isize) -> Option<usize> {
+        if let Some((center, radius)) = self.clip {
+            let (dx, dy, r) = (x - center.x, y - center.y, radius as isize);
+            if dx * dx + dy * dy > r * r {
+                return None;
+            }
+        }
+        self.index(x, y)
+    }
+
+    /// Plot one pixel at (x,y), ignoring if out of bounds or outside the clip.
+    pub fn put_pixel(&mut self, x: isize, y: isize, color: u32) {
+        if let Some(i) = self.draw_index(x, y) {
+            self.buf[i] = color;
+        }
+    }
+
+    /// Read back the pixel at (x,y), `None` if out of bounds.
+    pub fn get_pixel(&self, x: isize, y: isize) -> Option<u32> {
+        self.index(x, y).map(|i| self.buf[i])
+    }
+
+    /// Blend a `color_rgba` color over the pixel at (x,y) using its alpha byte, ignoring if out of bounds.
+    pub fn blend_pixel(&mut self, x: isize, y: isize, color: u32) {
+        let alpha = (color >> 24) as f32 / 255.0;
+        self.blend_coverage(x, y, color, alpha);
+    }
+
+    /// Blend `color` into the pixel at (x,y) by `coverage`, ignoring if out of bounds or outside the clip.
+    fn blend_coverage(&mut self, x: isize, y: isize, color: u32, coverage: f32) {
+        if coverage <= 0.0 {
             return;
         }
-        self.buf[y * self.width() + x] = color;
+        if let Some(i) = self.draw_index(x, y) {
+            self.buf[i] = blend(self.buf[i], color, coverage);
+        }
     }
 
+    /// Fill the disc of `radius` around `center`: every pixel with `dx² + dy² <= radius²`.
+    ///
+    /// `draw_line` stamps one of these per step, so it's filled a row at a time: the row `dy` away
+    /// from the center spans `isqrt(r² - dy²)` pixels to either side, filled as one slice by `fill_rect`
+    /// (which also does the clipping) instead of testing every pixel of the bounding box.
     pub fn draw_filled_circle(&mut self, center: Point, radius: usize, color: u32) {
-        let r=radius as isize;
+        let r = radius as isize;
         for dy in -r..=r {
-            for dx in -r..=r {
-                if dx*dx + dy*dy <= (r * r) {
+            let half = (r * r - dy * dy).isqrt();
+            let span = Dimensions { width: (2 * half + 1) as usize, height: 1 };
+            self.fill_rect(Point::new(center.x - half, center.y + dy), span, color);
+        }
+    }
+
+    /// Draw a circle outline (a ring) of the given thickness, centered on `radius`.
+    /// Fills every pixel whose distance to `center` is within `radius ± thickness/2`.
+    pub fn draw_circle(&mut self, center: Point, radius: isize, thickness: isize, color: u32) {
+        let half = thickness.max(1) / 2;
+        let r_inner = (radius - half).max(0);
+        let r_outer = radius + half;
+        if r_outer < 0 {
+            return;
+        }
+
+        // Compare squared distances, no sqrt needed.
+        // The band is widened by half a pixel on both sides (everything times 2 to stay in integers)
+        // so a ring of thickness 1 is still closed instead of a few scattered dots.
+        let inner_sq = (2 * r_inner - 1).max(0).pow(2);
+        let outer_sq = (2 * r_outer + 1).pow(2);
+        for dy in -r_outer..=r_outer {
+            for dx in -r_outer..=r_outer {
+                let d_sq = 4 * (dx*dx + dy*dy);
+                if d_sq >= inner_sq && d_sq < outer_sq {
                     self.put_pixel(center.x + dx, center.y + dy, color);
                 }
             }
         }
     }
 
+    /// Line from `a` to `b` with round ends, every pixel within `thickness / 2` of the segment
+    /// (so even thicknesses come out one pixel wider).
+    ///
+    /// A one pixel line is the plain Bresenham walk. Thicker ones are the rectangle along the line,
+    /// filled as a polygon, plus a disc at each end: each pixel gets filled about once, where
+    /// stamping a disc at every step filled most of them again at the next one.
     pub fn draw_line(&mut self, a: Point, b: Point, thickness: usize, color: u32) {
+        let radius = thickness / 2;
+        if radius == 0 {
+            self.draw_thin_line(a, b, color);
+            return;
+        }
+        // Out to the edge of the end discs' outermost pixels, so the body is as wide as the caps
+        self.fill_line_body(a, b, radius as f32 + 0.5, color);
+        self.draw_filled_circle(a, radius, color);
+        self.draw_filled_circle(b, radius, color);
+    }
+
+    /// One pixel wide line from `a` to `b`, Bresenham.
+    fn draw_thin_line(&mut self, a: Point, b: Point, color: u32) {
         let mut x0 = a.x;
         let mut y0 = a.y;
         let x1 = b.x;
@@ -121,12 +588,8 @@ impl<'a> Canvas<'a> {
         let sy = if y0 < y1 { 1 } else { -1 };
         let mut err = dx + dy;
 
-        // let radius = (thickness as f32 * 0.5).ceil() as isize;
-        let radius = thickness/2;
-
-
         loop {
-            self.draw_filled_circle(Point::new(x0, y0), radius, color);
+            self.put_pixel(x0, y0, color);
 
             if x0 == x1 && y0 == y1 { break; }
 
@@ -136,19 +599,698 @@ impl<'a> Canvas<'a> {
         }
     }
 
+    /// Fill the rectangle reaching `half` to either side of the segment from `a` to `b`,
+    /// cut square across at both ends. Nothing for a zero length segment, it has no direction.
+    fn fill_line_body(&mut self, a: Point, b: Point, half: f32, color: u32) {
+        let (dx, dy) = ((b.x - a.x) as f32, (b.y - a.y) as f32);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            return;
+        }
+        // Perpendicular to the line, `half` long
+        let (nx, ny) = (-dy / length * half, dx / length * half);
+        let offset = |p: Point, sign: f32| Point::new(
+            (p.x as f32 + sign * nx).round() as isize,
+            (p.y as f32 + sign * ny).round() as isize,
+        );
+        self.fill_polygon(&[offset(a, 1.0), offset(b, 1.0), offset(b, -1.0), offset(a, -1.0)], color);
+    }
+
+    /// Thick line from `a` to `b` with the given end caps.
+    /// Round caps are `draw_line`, flat ones fill the rectangle around the line as a polygon.
+    pub fn draw_line_capped(&mut self, a: Point, b: Point, thickness: usize, cap: LineCap, color: u32) {
+        if cap == LineCap::Round {
+            self.draw_line(a, b, thickness, color);
+            return;
+        }
+        if a.x == b.x && a.y == b.y {
+            // No direction to cut across, a square dot is the closest thing
+            let half = (thickness / 2) as isize;
+            let size = Dimensions { width: thickness.max(1), height: thickness.max(1) };
+            self.fill_rect(Point::new(a.x - half, a.y - half), size, color);
+            return;
+        }
+        // Half the thickness to either side, perpendicular to the line
+        self.fill_line_body(a, b, thickness.max(1) as f32 / 2.0, color);
+    }
+
+    /// Dashed line from `a` to `b`: dashes `dash_len` pixels long with `gap_len` pixels between them,
+    /// starting with a dash at `a`. The last one stops at `b`, shorter if that's where the line ends.
+    ///
+    /// The pattern is measured along the line rather than per axis, so the dashes come out the same
+    /// length at any angle. Each dash is a `draw_line_capped` with flat ends: round ones would make
+    /// every dash longer by the thickness. One pixel thin, a flat-ended body can come out zero pixels
+    /// wide on a diagonal, so those dashes are Bresenham walks instead. Dashes as long as the line
+    /// is thick make it dotted.
+    pub fn draw_dashed_line(&mut self, a: Point, b: Point, thickness: usize, color: u32, dash_len: usize, gap_len: usize) {
+        let (dx, dy) = ((b.x - a.x) as f32, (b.y - a.y) as f32);
+        let length = dx.hypot(dy);
+        if dash_len == 0 || length == 0.0 {
+            return;
+        }
+        // The point `distance` pixels from `a` towards `b`
+        let along = |distance: f32| Point::new(
+            (a.x as f32 + dx * distance / length).round() as isize,
+            (a.y as f32 + dy * distance / length).round() as isize,
+        );
+        let mut start = 0.0;
+        while start < length {
+            let end = (start + dash_len as f32).min(length);
+            if thickness <= 1 {
+                // The walk takes both end pixels, the dash's last one is a pixel before `end`
+                self.draw_thin_line(along(start), along((end - 1.0).max(start)), color);
+            } else {
+                self.draw_line_capped(along(start), along(end), thickness, LineCap::Flat, color);
+            }
+            start = end + gap_len as f32;
+        }
+    }
+
+    /// Fill the polygon with corners `points` (in order, either direction), clipped to the canvas.
+    ///
+    /// Scanline fill with an active edge list: edges join the list at the row of their top end and
+    /// leave it at their bottom end, every row fills between pairs of crossings. Meant for convex
+    /// shapes, other simple polygons come out even-odd filled.
+    /// A pixel is filled when its center is inside. Points on the left and top edges count as
+    /// inside, points on the right and bottom edges don't. So two polygons sharing an edge never
+    /// overlap, and a square from (0,0) to (4,4) fills 4x4 pixels like `fill_rect` does.
+    pub fn fill_polygon(&mut self, points: &[Point], color: u32) {
+        if points.len() < 3 {
+            return;
+        }
+
+        // Every non-horizontal edge as (top y, bottom y, x at the top, x step per row), sorted by top.
+        // Horizontal edges add nothing, the edges meeting them already bound the span.
+        let mut edges: Vec<(isize, isize, f32, f32)> = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .filter(|(p, q)| p.y != q.y)
+            .map(|(&p, &q)| {
+                let (top, bottom) = if p.y < q.y { (p, q) } else { (q, p) };
+                let step = (bottom.x - top.x) as f32 / (bottom.y - top.y) as f32;
+                (top.y, bottom.y, top.x as f32, step)
+            })
+            .collect();
+        edges.sort_by_key(|edge| edge.0);
+
+        let first_row = edges.first().map_or(0, |edge| edge.0).max(0);
+        let last_row = edges.iter().map(|edge| edge.1).max().unwrap_or(0).min(self.height() as isize);
+        let mut active = Vec::new();
+        let mut next = 0;
+        let mut crossings = Vec::new();
+        for y in first_row..last_row {
+            // Edges that reach this row join, ones that ended above it leave
+            while next < edges.len() && edges[next].0 <= y {
+                active.push(edges[next]);
+                next += 1;
+            }
+            active.retain(|edge| edge.1 > y);
+
+            crossings.clear();
+            crossings.extend(active.iter().map(|&(top, _, x, step)| x + (y - top) as f32 * step));
+            crossings.sort_by(f32::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                // Pixels whose center lies in [left, right)
+                let x0 = pair[0].ceil() as isize;
+                let x1 = pair[1].ceil() as isize;
+                if x1 > x0 {
+                    self.fill_rect(Point::new(x0, y), Dimensions { width: (x1 - x0) as usize, height: 1 }, color);
+                }
+            }
+        }
+    }
+
+    /// Anti-aliased line (Xiaolin Wu's algorithm, widened to `thickness`).
+    /// Walks the major axis one pixel at a time and, per column, covers the span of the line
+    /// with partial coverage on the two edge pixels, blended against what's already there.
+    #[cfg(feature = "aa")]
+    pub fn draw_line_aa(&mut self, a: Point, b: Point, thickness: usize, color: u32) {
+        let (mut x0, mut y0, mut x1, mut y1) = (a.x as f32, a.y as f32, b.x as f32, b.y as f32);
+
+        // Always walk along the longer axis, left to right
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 0.0 } else { (y1 - y0) / dx };
+
+        // Thickness is perpendicular to the line, per column we need the (longer) vertical extent
+        let half = thickness.max(1) as f32 * (1.0 + gradient * gradient).sqrt() / 2.0;
+
+        let mut y = y0;
+        for x in (x0 as isize)..=(x1 as isize) {
+            // Pixel row j covers [j - 0.5, j + 0.5], the line covers [y - half, y + half]
+            let top = y - half;
+            let bottom = y + half;
+            for j in (top.round() as isize)..=(bottom.round() as isize) {
+                let overlap = (bottom.min(j as f32 + 0.5) - top.max(j as f32 - 0.5)).clamp(0.0, 1.0);
+                if steep {
+                    self.blend_coverage(j, x, color, overlap);
+                } else {
+                    self.blend_coverage(x, j, color, overlap);
+                }
+            }
+            y += gradient;
+        }
+    }
+
+    /// Stroke a circular arc of `radius` around `center`, from `start_angle` clockwise to `end_angle` (radians,
+    /// same orientation as `Point::polar`). An `end_angle` below `start_angle` wraps around through 0.
+    pub fn draw_arc(&mut self, center: Point, radius: usize, start_angle: f32, end_angle: f32, thickness: usize, color: u32) {
+        let mut sweep = end_angle - start_angle;
+        if sweep < 0.0 {
+            sweep += std::f32::consts::TAU;
+        }
+
+        // One step per pixel of arc length, so neighbouring stamps always touch however large the radius
+        let steps = ((sweep * radius as f32).ceil() as usize).max(1);
+        let stamp_radius = thickness / 2;
+        for i in 0..=steps {
+            let angle = start_angle + sweep * (i as f32 / steps as f32);
+            self.draw_filled_circle(center.polar(angle, radius as f32), stamp_radius, color);
+        }
+    }
+
+    /// Draw a radial tick: a short line along `angle` from `inner_radius` to `outer_radius` around `center`.
+    pub fn draw_tick(&mut self, center: Point, inner_radius: usize, outer_radius: usize, angle: f32, thickness: usize, color: u32) {
+        // Both ends are rounded to whole pixels by polar(), so the ticks don't jitter on resize
+        let inner = center.polar(angle, inner_radius as f32);
+        let outer = center.polar(angle, outer_radius as f32);
+        self.draw_line(inner, outer, thickness, color);
+    }
+
+    /// Draw `text` with the built-in 5x7 font, `origin` is the top-left corner.
+    /// Every font pixel becomes a `scale` x `scale` block. Characters the font lacks are left blank.
+    pub fn draw_text(&mut self, origin: Point, text: &str, scale: usize, color: u32) {
+        let scale = scale.max(1);
+        let advance = ((font::GLYPH_WIDTH + font::GLYPH_SPACING) * scale) as isize;
+
+        for (i, c) in text.chars().enumerate() {
+            let Some(glyph) = font::glyph(c) else { continue };
+            let left = origin.x + i as isize * advance;
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..font::GLYPH_WIDTH {
+                    // Bit 4 is the leftmost column
+                    if bits & (1 << (font::GLYPH_WIDTH - 1 - col)) == 0 {
+                        continue;
+                    }
+                    let x = left + (col * scale) as isize;
+                    let y = origin.y + (row * scale) as isize;
+                    for dy in 0..scale as isize {
+                        for dx in 0..scale as isize {
+                            self.put_pixel(x + dx, y + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stroke the outline of a rectangle of `size` with its top-left corner at `top_left`.
+    /// The sides are `draw_line`s through the outermost pixels of the rectangle, so a thick
+    /// border reaches half its thickness outside and has rounded corners.
+    pub fn draw_rect(&mut self, top_left: Point, size: Dimensions, thickness: usize, color: u32) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        let right  = top_left.x + size.width as isize - 1;
+        let bottom = top_left.y + size.height as isize - 1;
+
+        let top_right    = Point::new(right, top_left.y);
+        let bottom_left  = Point::new(top_left.x, bottom);
+        let bottom_right = Point::new(right, bottom);
+
+        self.draw_line(top_left, top_right, thickness, color);
+        self.draw_line(top_left, bottom_left, thickness, color);
+        self.draw_line(bottom_left, bottom_right, thickness, color);
+        self.draw_line(bottom_right, top_right, thickness, color);
+    }
+
+    /// Border `padding` pixels in from the canvas edges.
     pub fn draw_frame(&mut self, padding: usize, thickness: usize, color: u32) {
-        let w = self.max_x() as isize;
-        let h = self.max_y() as isize;
+        let size = Dimensions {
+            width:  (self.max_x() + 1).saturating_sub(2 * padding),
+            height: (self.max_y() + 1).saturating_sub(2 * padding),
+        };
         let p = padding as isize;
+        self.draw_rect(Point::new(p, p), size, thickness, color);
+    }
+
+    /// Border like `draw_frame` that looks beveled: the top and left sides are `base_color` with
+    /// `light_delta` added to each channel, the bottom and right ones with `dark_delta` taken off.
+    /// Positive deltas make a raised bezel lit from the top left, negative ones an inset one.
+    ///
+    /// Each side is a trapezoid with mitred corners, so the shades meet on the diagonals.
+    pub fn draw_bezel(&mut self, padding: usize, thickness: usize, base_color: u32, light_delta: i32, dark_delta: i32) {
+        // Same footprint as draw_frame: the band straddles the line `padding` pixels in
+        let half = (thickness / 2) as isize;
+        let outer_x0 = padding as isize - half;
+        let outer_y0 = outer_x0;
+        let outer_x1 = self.width() as isize - outer_x0;
+        let outer_y1 = self.height() as isize - outer_y0;
+        let t = thickness as isize;
+        let (inner_x0, inner_y0, inner_x1, inner_y1) = (outer_x0 + t, outer_y0 + t, outer_x1 - t, outer_y1 - t);
+        if inner_x0 >= inner_x1 || inner_y0 >= inner_y1 {
+            return;
+        }
+
+        let light = offset_channels(base_color, light_delta);
+        let dark = offset_channels(base_color, -dark_delta);
+        let corner = |x, y| Point::new(x, y);
+        let sides = [
+            // top, left, bottom, right
+            ([corner(outer_x0, outer_y0), corner(outer_x1, outer_y0), corner(inner_x1, inner_y0), corner(inner_x0, inner_y0)], light),
+            ([corner(outer_x0, outer_y0), corner(inner_x0, inner_y0), corner(inner_x0, inner_y1), corner(outer_x0, outer_y1)], light),
+            ([corner(inner_x0, inner_y1), corner(inner_x1, inner_y1), corner(outer_x1, outer_y1), corner(outer_x0, outer_y1)], dark),
+            ([corner(inner_x1, inner_y0), corner(outer_x1, outer_y0), corner(outer_x1, outer_y1), corner(inner_x1, inner_y1)], dark),
+        ];
+        for (points, color) in sides {
+            self.fill_polygon(&points, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: u32 = color_rgb(255, 255, 255);
+
+    /// A black `width` x `height` pixel buffer to wrap in a canvas with `canvas`.
+    fn blank(width: usize, height: usize) -> Vec<u32> {
+        vec![0; width * height]
+    }
+
+    fn canvas(buf: &mut [u32], width: usize, height: usize) -> Canvas<'_> {
+        Canvas::new(buf, Dimensions { width, height }).expect("buffer matches the size")
+    }
+
+    fn is_set(canvas: &Canvas, x: isize, y: isize) -> bool {
+        canvas.get_pixel(x, y) == Some(WHITE)
+    }
+
+    /// How many pixels of `canvas` are `color`.
+    fn count(canvas: &Canvas, color: u32) -> usize {
+        canvas.buf.iter().filter(|&&pixel| pixel == color).count()
+    }
+
+    #[test]
+    fn color_rgb_packs_channels_as_0x00rrggbb() {
+        assert_eq!(color_rgb(0x12, 0x34, 0x56), 0x0012_3456);
+        assert_eq!(color_rgb(255, 0, 0), 0x00FF_0000);
+        assert_eq!(color_rgb(0, 255, 0), 0x0000_FF00);
+        assert_eq!(color_rgb(0, 0, 255), 0x0000_00FF);
+        assert_eq!(color_rgb(255, 255, 255) >> 24, 0, "the top byte stays clear");
+    }
+
+    #[test]
+    fn put_pixel_ignores_pixels_off_the_canvas() {
+        let mut buf = blank(4, 3);
+        let mut canvas = canvas(&mut buf, 4, 3);
+        for (x, y) in [(-1, 0), (0, -1), (4, 0), (0, 3), (isize::MIN, isize::MAX)] {
+            canvas.put_pixel(x, y, WHITE);
+        }
+        assert!(canvas.buf.iter().all(|&pixel| pixel == 0));
+
+        canvas.put_pixel(3, 2, WHITE);
+        assert_eq!(canvas.buf[2 * 4 + 3], WHITE);
+        assert_eq!(count(&canvas, WHITE), 1);
+    }
+
+    #[test]
+    fn draw_line_reaches_both_endpoints() {
+        let ends = [
+            (Point::new(2, 3), Point::new(17, 9)),   // shallow
+            (Point::new(4, 1), Point::new(9, 18)),   // steep
+            (Point::new(16, 15), Point::new(3, 2)),  // right to left, upwards
+            (Point::new(5, 10), Point::new(14, 10)), // horizontal
+        ];
+        for thickness in [1, 4] {
+            for (a, b) in ends {
+                let mut buf = blank(20, 20);
+                let mut canvas = canvas(&mut buf, 20, 20);
+                canvas.draw_line(a, b, thickness, WHITE);
+                assert!(is_set(&canvas, a.x, a.y), "start {a:?} of a {thickness} px line to {b:?}");
+                assert!(is_set(&canvas, b.x, b.y), "end {b:?} of a {thickness} px line from {a:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_filled_circle_covers_the_disc_of_its_radius() {
+        for radius in [0, 1, 4, 9] {
+            let mut buf = blank(21, 21);
+            let mut canvas = canvas(&mut buf, 21, 21);
+            canvas.draw_filled_circle(Point::new(10, 10), radius, WHITE);
+            let r = radius as isize;
+            for y in 0..21 {
+                for x in 0..21 {
+                    let (dx, dy) = (x - 10, y - 10);
+                    let inside = dx * dx + dy * dy <= r * r;
+                    assert_eq!(is_set(&canvas, x, y), inside, "({x}, {y}) at radius {radius}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_circle_sets_the_ring_but_not_the_center() {
+        for thickness in [1, 3] {
+            let mut buf = blank(21, 21);
+            let mut canvas = canvas(&mut buf, 21, 21);
+            canvas.draw_circle(Point::new(10, 10), 8, thickness, WHITE);
+            for (x, y) in [(10, 2), (10, 18), (2, 10), (18, 10)] {
+                assert!(is_set(&canvas, x, y), "({x}, {y}) on a ring {thickness} px thick");
+            }
+            assert!(!is_set(&canvas, 10, 10), "center of a ring {thickness} px thick");
+        }
+    }
+
+    #[test]
+    fn draw_circle_clips_at_the_canvas_edges() {
+        let mut buf = blank(10, 10);
+        let mut canvas = canvas(&mut buf, 10, 10);
+        canvas.draw_circle(Point::new(0, 0), 6, 1, WHITE);
+        assert!(is_set(&canvas, 6, 0) && is_set(&canvas, 0, 6));
+        assert!(!is_set(&canvas, 0, 0));
+    }
+
+    #[test]
+    fn canvas_new_refuses_a_buffer_of_the_wrong_size() {
+        for len in [0, 47, 49, 6 * 6] {
+            let mut buf = vec![0; len];
+            match Canvas::new(&mut buf, Dimensions { width: 8, height: 6 }) {
+                Err(SizeMismatch { expected, actual }) => assert_eq!((expected, actual), (48, len)),
+                Ok(_) => panic!("a {len} pixel buffer was taken for 8x6"),
+            }
+        }
+        let mut buf = blank(8, 6);
+        assert!(Canvas::new(&mut buf, Dimensions { width: 8, height: 6 }).is_ok());
+    }
+
+    #[test]
+    fn fill_rect_fills_a_rect_inside_the_canvas() {
+        let mut buf = blank(10, 8);
+        let mut canvas = canvas(&mut buf, 10, 8);
+        canvas.fill_rect(Point::new(2, 3), Dimensions { width: 4, height: 2 }, WHITE);
+        assert_eq!(count(&canvas, WHITE), 8);
+        assert!(is_set(&canvas, 2, 3) && is_set(&canvas, 5, 4));
+        assert!(!is_set(&canvas, 1, 3) && !is_set(&canvas, 6, 4) && !is_set(&canvas, 2, 2) && !is_set(&canvas, 2, 5));
+    }
+
+    #[test]
+    fn fill_rect_clips_at_each_edge() {
+        // Top-left corner, and the pixels of the 4x4 rect left on the 10x8 canvas
+        let cases = [
+            (Point::new(-2, 3), 2 * 4),  // off the left
+            (Point::new(8, 3), 2 * 4),   // off the right
+            (Point::new(3, -3), 4),      // off the top
+            (Point::new(3, 7), 4),       // off the bottom
+            (Point::new(-1, -1), 3 * 3), // off a corner
+        ];
+        for (top_left, pixels) in cases {
+            let mut buf = blank(10, 8);
+            let mut canvas = canvas(&mut buf, 10, 8);
+            canvas.fill_rect(top_left, Dimensions { width: 4, height: 4 }, WHITE);
+            assert_eq!(count(&canvas, WHITE), pixels, "4x4 at {top_left:?}");
+        }
+    }
+
+    #[test]
+    fn fill_rect_outside_the_canvas_draws_nothing() {
+        let outside = [Point::new(-4, 0), Point::new(10, 0), Point::new(0, -4), Point::new(0, 8), Point::new(isize::MAX, isize::MAX)];
+        for top_left in outside {
+            let mut buf = blank(10, 8);
+            let mut canvas = canvas(&mut buf, 10, 8);
+            canvas.fill_rect(top_left, Dimensions { width: 4, height: 4 }, WHITE);
+            assert_eq!(count(&canvas, WHITE), 0, "4x4 at {top_left:?}");
+        }
+        let mut buf = blank(10, 8);
+        let mut canvas = canvas(&mut buf, 10, 8);
+        canvas.fill_rect(Point::new(2, 2), Dimensions { width: 0, height: 3 }, WHITE);
+        assert_eq!(count(&canvas, WHITE), 0, "zero width");
+    }
+
+    #[test]
+    fn vertical_gradient_runs_from_top_to_bottom_row() {
+        let (top, bottom) = (color_rgb(0, 100, 255), color_rgb(255, 200, 0));
+        let mut buf = blank(3, 5);
+        let mut canvas = canvas(&mut buf, 3, 5);
+        canvas.fill_vertical_gradient(top, bottom);
+        for x in 0..3 {
+            assert_eq!(canvas.get_pixel(x, 0), Some(top));
+            // Halfway each channel is the rounded mean, 127.5 rounds up
+            assert_eq!(canvas.get_pixel(x, 2), Some(color_rgb(128, 150, 128)));
+            assert_eq!(canvas.get_pixel(x, 4), Some(bottom));
+        }
+    }
+
+    #[test]
+    fn horizontal_gradient_runs_from_left_to_right_column() {
+        let (left, right) = (color_rgb(0, 0, 0), color_rgb(200, 100, 50));
+        let mut buf = blank(5, 2);
+        let mut canvas = canvas(&mut buf, 5, 2);
+        canvas.fill_horizontal_gradient(left, right);
+        for y in 0..2 {
+            assert_eq!(canvas.get_pixel(0, y), Some(left));
+            assert_eq!(canvas.get_pixel(2, y), Some(color_rgb(100, 50, 25)));
+            assert_eq!(canvas.get_pixel(4, y), Some(right));
+        }
+    }
+
+    #[test]
+    fn get_pixel_reads_back_what_was_written() {
+        let mut buf = blank(6, 4);
+        let mut canvas = canvas(&mut buf, 6, 4);
+        let color = color_rgb(1, 2, 3);
+        for (x, y) in [(0, 0), (5, 0), (0, 3), (5, 3), (2, 1)] {
+            canvas.put_pixel(x, y, color);
+            assert_eq!(canvas.get_pixel(x, y), Some(color), "({x}, {y})");
+        }
+        assert_eq!(canvas.get_pixel(3, 1), Some(0), "an untouched pixel");
+    }
+
+    #[test]
+    fn get_pixel_off_the_canvas_is_none() {
+        let mut buf = blank(6, 4);
+        let canvas = canvas(&mut buf, 6, 4);
+        for (x, y) in [(-1, 0), (0, -1), (6, 0), (0, 4), (6, 4), (isize::MIN, 0), (0, isize::MAX)] {
+            assert_eq!(canvas.get_pixel(x, y), None, "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn fill_polygon_fills_inside_a_triangle_only() {
+        let mut buf = blank(20, 20);
+        let mut canvas = canvas(&mut buf, 20, 20);
+        canvas.fill_polygon(&[Point::new(2, 2), Point::new(17, 2), Point::new(2, 17)], WHITE);
+        for (x, y) in [(3, 3), (8, 8), (14, 3), (3, 14), (2, 2)] {
+            assert!(is_set(&canvas, x, y), "({x}, {y}) inside");
+        }
+        for (x, y) in [(1, 5), (5, 1), (11, 11), (16, 16), (17, 3), (3, 17)] {
+            assert!(!is_set(&canvas, x, y), "({x}, {y}) outside");
+        }
+    }
+
+    #[test]
+    fn fill_polygon_fills_a_square_like_fill_rect() {
+        // The right and bottom edges stay out
+        let mut buf = blank(10, 10);
+        let mut canvas = canvas(&mut buf, 10, 10);
+        canvas.fill_polygon(&[Point::new(2, 2), Point::new(6, 2), Point::new(6, 6), Point::new(2, 6)], WHITE);
+        assert_eq!(count(&canvas, WHITE), 16);
+        assert!(is_set(&canvas, 2, 2) && is_set(&canvas, 5, 5));
+        assert!(!is_set(&canvas, 6, 5) && !is_set(&canvas, 5, 6));
+    }
+
+    #[test]
+    fn fill_polygon_fills_inside_a_quad_only() {
+        // A diamond, corners given counterclockwise
+        let mut buf = blank(21, 21);
+        let mut canvas = canvas(&mut buf, 21, 21);
+        canvas.fill_polygon(&[Point::new(10, 2), Point::new(2, 10), Point::new(10, 18), Point::new(18, 10)], WHITE);
+        for (x, y) in [(10, 10), (10, 3), (3, 10), (10, 17), (17, 10), (13, 13)] {
+            assert!(is_set(&canvas, x, y), "({x}, {y}) inside");
+        }
+        for (x, y) in [(3, 3), (17, 3), (3, 17), (17, 17), (15, 15), (10, 19)] {
+            assert!(!is_set(&canvas, x, y), "({x}, {y}) outside");
+        }
+    }
+
+    #[test]
+    fn fill_polygon_clips_to_the_canvas() {
+        let mut buf = blank(10, 10);
+        let mut canvas = canvas(&mut buf, 10, 10);
+        canvas.fill_polygon(&[Point::new(-20, -20), Point::new(30, -20), Point::new(30, 30), Point::new(-20, 30)], WHITE);
+        assert_eq!(count(&canvas, WHITE), 100);
+    }
 
-        let top_left    = Point::new(p,     p); 
-        let top_right   = Point::new(w - p, p);
-        let bottom_left = Point::new(p,     h - p);
-        let bottom_right= Point::new(w-p,   h - p);
+    #[test]
+    fn color_helpers_clamp_channels_to_0_255() {
+        let color = color_rgb(100, 200, 50);
+        assert_eq!(adjust_brightness(color, 2.0), color_rgb(200, 255, 100));
+        assert_eq!(adjust_brightness(color, 10.0), color_rgb(255, 255, 255));
+        assert_eq!(adjust_brightness(color, 0.0), color_rgb(0, 0, 0));
+        assert_eq!(adjust_brightness(color, -1.0), color_rgb(0, 0, 0));
+        assert_eq!(adjust_brightness(color, 0.5), color_rgb(50, 100, 25));
+        assert_eq!(with_channels(color, |r, g, _| (r - 300.0, g + 300.0, f32::NAN)), color_rgb(0, 255, 0));
+        assert_eq!(with_channels(color, |r, g, b| (g, r, b)), color_rgb(200, 100, 50));
+        // The alpha byte of the input doesn't carry over
+        assert_eq!(with_channels(0xFF00_0000 | color, |r, g, b| (r, g, b)), color);
+    }
+
+    #[test]
+    fn lerp_color_goes_from_a_to_b() {
+        let (a, b) = (color_rgb(0, 100, 255), color_rgb(255, 200, 0));
+        assert_eq!(lerp_color(a, b, 0.0), a);
+        assert_eq!(lerp_color(a, b, 0.5), color_rgb(128, 150, 128));
+        assert_eq!(lerp_color(a, b, 1.0), b);
+        // Past either end it stays at that end
+        assert_eq!(lerp_color(a, b, -1.0), a);
+        assert_eq!(lerp_color(a, b, 2.0), b);
+    }
+
+    /// The disc the way draw_filled_circle drew it before it filled row spans: every pixel of the
+    /// bounding box tested on its own.
+    fn naive_filled_circle(canvas: &mut Canvas, center: Point, radius: usize, color: u32) {
+        let r = radius as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r {
+                    canvas.put_pixel(center.x + dx, center.y + dy, color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_filled_circle_matches_the_naive_disc() {
+        // Centered, and hanging off the corners and edges so the spans get clipped
+        let centers = [Point::new(20, 15), Point::new(0, 0), Point::new(39, 29), Point::new(-3, 12), Point::new(25, 33)];
+        for radius in [0, 1, 2, 3, 5, 8, 13, 21, 50] {
+            for center in centers {
+                let (mut spans, mut naive) = (blank(40, 30), blank(40, 30));
+                canvas(&mut spans, 40, 30).draw_filled_circle(center, radius, WHITE);
+                naive_filled_circle(&mut canvas(&mut naive, 40, 30), center, radius, WHITE);
+                assert!(spans == naive, "radius {radius} at {center:?}");
+            }
+        }
+
+        // Inside a circular clip, which fill_rect applies to whole spans
+        let (mut spans, mut naive) = (blank(40, 30), blank(40, 30));
+        let clip = (Point::new(18, 14), 11);
+        canvas(&mut spans, 40, 30).with_circular_clip(clip.0, clip.1, |canvas| canvas.draw_filled_circle(Point::new(26, 10), 9, WHITE));
+        canvas(&mut naive, 40, 30).with_circular_clip(clip.0, clip.1, |canvas| naive_filled_circle(canvas, Point::new(26, 10), 9, WHITE));
+        assert!(spans == naive, "inside a clip");
+    }
+
+    const RED: u32 = color_rgb(255, 0, 0);
+    const BLUE: u32 = color_rgb(0, 0, 255);
+
+    /// A `size` x `size` checkerboard, red in the top-left corner.
+    fn checkerboard(size: usize) -> Vec<u32> {
+        (0..size * size).map(|i| if (i % size + i / size).is_multiple_of(2) { RED } else { BLUE }).collect()
+    }
+
+    #[test]
+    fn blit_copies_a_checkerboard_clipped_at_each_edge() {
+        let board = checkerboard(4);
+        let board_size = Dimensions { width: 4, height: 4 };
+        // Inside, off each edge, off two corners and all the way off
+        let offsets = [(3, 2), (-2, 2), (8, 2), (3, -3), (3, 6), (-1, -1), (7, 5), (-4, 2), (3, 8)];
+        for (left, top) in offsets {
+            let mut buf = blank(10, 8);
+            let mut canvas = canvas(&mut buf, 10, 8);
+            let rect = canvas.blit(&board, board_size, Point::new(left, top), false);
+
+            for y in 0..8 {
+                for x in 0..10 {
+                    let (sx, sy) = (x - left, y - top);
+                    let expected = if (0..4).contains(&sx) && (0..4).contains(&sy) { board[(sy * 4 + sx) as usize] } else { 0 };
+                    assert_eq!(canvas.get_pixel(x, y), Some(expected), "({x}, {y}) with the board at ({left}, {top})");
+                }
+            }
+            let covered = canvas.buf.iter().filter(|&&pixel| pixel != 0).count();
+            assert_eq!(rect.width() * rect.height(), covered, "returned rect with the board at ({left}, {top})");
+        }
+    }
+
+    #[test]
+    fn blit_scaled_stretches_every_source_pixel() {
+        let board = checkerboard(4);
+        let board_size = Dimensions { width: 4, height: 4 };
+        let mut buf = blank(10, 8);
+        let mut canvas = canvas(&mut buf, 10, 8);
+        // Twice the size, hanging off the right and bottom edges
+        let rect = canvas.blit_scaled(&board, board_size, Point::new(3, 1), Dimensions { width: 8, height: 8 }, Sampling::Nearest, false);
+        assert_eq!(rect, Rect { x0: 3, y0: 1, x1: 10, y1: 8 });
+        for y in 1..8 {
+            for x in 3..10 {
+                let (sx, sy) = ((x - 3) / 2, (y - 1) / 2);
+                assert_eq!(canvas.get_pixel(x, y), Some(board[(sy * 4 + sx) as usize]), "({x}, {y})");
+            }
+        }
+        assert_eq!(canvas.get_pixel(2, 1), Some(0));
+    }
+
+    #[test]
+    fn bilinear_blit_at_the_source_size_is_a_plain_copy() {
+        // Every canvas pixel lands on a source pixel center, nothing to mix
+        let board = checkerboard(4);
+        let board_size = Dimensions { width: 4, height: 4 };
+        let (mut bilinear, mut copied) = (blank(10, 8), blank(10, 8));
+        canvas(&mut bilinear, 10, 8).blit_scaled(&board, board_size, Point::new(-1, 5), board_size, Sampling::Bilinear, false);
+        canvas(&mut copied, 10, 8).blit(&board, board_size, Point::new(-1, 5), false);
+        assert!(bilinear == copied);
+    }
+
+    #[test]
+    fn blit_blends_by_the_source_alpha() {
+        // Opaque red squares and clear ones in between
+        let board: Vec<u32> = checkerboard(2).iter().map(|&pixel| if pixel == RED { 0xFF00_0000 | RED } else { BLUE }).collect();
+        let mut buf = vec![WHITE; 4 * 4];
+        let mut canvas = canvas(&mut buf, 4, 4);
+        canvas.blit(&board, Dimensions { width: 2, height: 2 }, Point::new(1, 1), true);
+        assert_eq!(canvas.get_pixel(1, 1), Some(RED));
+        assert_eq!(canvas.get_pixel(2, 1), Some(WHITE), "a clear pixel leaves the canvas");
+        assert_eq!(canvas.get_pixel(2, 2), Some(RED));
+    }
+
+    #[test]
+    fn dashed_line_alternates_dashes_and_gaps() {
+        // 4 pixels on, 3 off, along a 29 pixel line: the last dash is cut short at the end
+        for thickness in [1, 3] {
+            let mut buf = blank(32, 5);
+            let mut canvas = canvas(&mut buf, 32, 5);
+            canvas.draw_dashed_line(Point::new(0, 2), Point::new(29, 2), thickness, WHITE, 4, 3);
+            let rows = if thickness == 1 { 2..3 } else { 1..4 };
+            for y in 0..5 {
+                for x in 0..32 {
+                    let on = rows.contains(&y) && x < 29 && x % 7 < 4;
+                    assert_eq!(is_set(&canvas, x, y), on, "({x}, {y}) on a line {thickness} px thick");
+                }
+            }
+        }
+    }
 
-        self.draw_line(top_left,top_right, thickness, color); 
-        self.draw_line(top_left,bottom_left, thickness, color); 
-        self.draw_line(bottom_left,bottom_right, thickness, color); 
-        self.draw_line(bottom_right,top_right, thickness, color); 
+    #[test]
+    fn dashed_line_dashes_are_as_long_on_a_diagonal() {
+        // Along x = y every pixel is sqrt(2) long, a 7 pixel dash covers 5 of them
+        let mut buf = blank(40, 40);
+        let mut canvas = canvas(&mut buf, 40, 40);
+        canvas.draw_dashed_line(Point::new(0, 0), Point::new(35, 35), 1, WHITE, 7, 7);
+        let lit: Vec<bool> = (0..36).map(|i| is_set(&canvas, i, i)).collect();
+        let first_gap = lit.iter().position(|&on| !on).unwrap();
+        assert_eq!(first_gap, 5);
+        assert_eq!(lit[5..10], [false; 5]);
+        assert!(lit[10]);
     }
 }
@@ -0,0 +1,34 @@
+// What can go wrong between the app and the windowing system, with the step it went wrong at.
+//
+// None of these are expected in normal use. They mean the display went away, the platform
+// refused a window, or softbuffer couldn't map the frame. main.rs reports them with
+// a clear message instead of a panic, and decides whether to try the next frame or exit.
+
+use std::fmt;
+
+use softbuffer::SoftBufferError;
+use winit::error::{EventLoopError, OsError};
+
+pub enum AppError {
+    EventLoop(EventLoopError),      // creating or running the event loop
+    CreateWindow(OsError),
+    Context(SoftBufferError),       // connecting softbuffer to the display
+    Surface(SoftBufferError),       // the surface drawn into, on the window
+    Resize(u32, u32, SoftBufferError), // resizing the surface to width x height
+    Buffer(SoftBufferError),        // getting the buffer of the next frame
+    Present(SoftBufferError),       // showing a drawn frame
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::EventLoop(e) => write!(f, "Event loop failed: {e}"),
+            AppError::CreateWindow(e) => write!(f, "Could not create the window: {e}"),
+            AppError::Context(e) => write!(f, "Could not connect to the display for drawing: {e}"),
+            AppError::Surface(e) => write!(f, "Could not create the surface to draw on: {e}"),
+            AppError::Resize(width, height, e) => write!(f, "Could not resize the surface to {width}x{height}: {e}"),
+            AppError::Buffer(e) => write!(f, "Could not get a buffer for the frame: {e}"),
+            AppError::Present(e) => write!(f, "Could not show the frame: {e}"),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// A tiny 5x7 bitmap font, embedded so no font file is needed.
+//
+// Each glyph is 7 rows of 5 bits. Bit 4 is the leftmost pixel, bit 0 the rightmost:
+//   0b01110  ->  . # # # .
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+/// Blank columns between two glyphs (before scaling).
+pub const GLYPH_SPACING: usize = 1;
+
+type Glyph = [u8; GLYPH_HEIGHT];
+
+const DIGITS: [Glyph; 10] = [
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110], // 0
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // 1
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111], // 2
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110], // 3
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010], // 4
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110], // 5
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110], // 6
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000], // 7
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110], // 8
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100], // 9
+];
+
+// Capitals only, lowercase letters are drawn as capitals too
+const LETTERS: [Glyph; 26] = [
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // A
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110], // B
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110], // C
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100], // D
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111], // E
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000], // F
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111], // G
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // H
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // I
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // J
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001], // K
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111], // L
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001], // M
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001], // N
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // O
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000], // P
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101], // Q
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001], // R
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110], // S
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // T
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // U
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // V
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010], // W
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001], // X
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100], // Y
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111], // Z
+];
+
+// Signs for UTC offsets like "+03" that stand in for a time zone abbreviation
+const PLUS: Glyph = [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000];
+const MINUS: Glyph = [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000];
+const COLON: Glyph = [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000];
+const DOT: Glyph = [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100];
+const SPACE: Glyph = [0; GLYPH_HEIGHT];
+// For the key help
+const QUESTION: Glyph = [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100];
+const SLASH: Glyph = [0b00001, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b10000];
+const LEFT_BRACKET: Glyph = [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110];
+const RIGHT_BRACKET: Glyph = [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110];
+
+/// Bitmap for a character, `None` if the font doesn't have it.
+pub fn glyph(c: char) -> Option<&'static Glyph> {
+    match c {
+        '0'..='9' => Some(&DIGITS[c as usize - '0' as usize]),
+        'A'..='Z' => Some(&LETTERS[c as usize - 'A' as usize]),
+        'a'..='z' => Some(&LETTERS[c as usize - 'a' as usize]),
+        '+' => Some(&PLUS),
+        '-' => Some(&MINUS),
+        ':' => Some(&COLON),
+        '.' => Some(&DOT),
+        ' ' => Some(&SPACE),
+        '?' => Some(&QUESTION),
+        '/' => Some(&SLASH),
+        '[' => Some(&LEFT_BRACKET),
+        ']' => Some(&RIGHT_BRACKET),
+        _ => None,
+    }
+}
+
+/// Width in pixels of `text` drawn at `scale`, without trailing spacing.
+pub fn text_width(text: &str, scale: usize) -> usize {
+    let chars = text.chars().count();
+    if chars == 0 {
+        return 0;
+    }
+    (chars * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING) * scale
+}
+
+/// Height in pixels of a line of text drawn at `scale`.
+pub fn text_height(scale: usize) -> usize {
+    GLYPH_HEIGHT * scale
+}
@@ -0,0 +1,127 @@
+// The key help: every key of the shown scene in a panel over it, toggled with F1 or ? and closed
+// with Escape too. Drawn as part of the hud, so the scene goes on moving behind it and keeps taking
+// its keys and clicks.
+//
+// The keys are listed here and nowhere else, next to each other so a missing one stands out.
+// A key added to (or changed in) a match in main.rs or a scene's `key` goes in here too.
+
+use watchrs::draw::{Canvas, Dimensions, Point, Rect};
+use watchrs::font;
+use crate::layout::Layout;
+use crate::scene::SceneKind;
+use crate::theme::ThemePalette;
+
+/// A key, or a few that do the same, and what it does.
+pub type Binding = (&'static str, &'static str);
+
+// How much of the scene the panel covers, the rest shows through
+const PANEL_OPACITY: f32 = 0.85;
+
+// Handled in main.rs for every scene
+const APP_KEYS: &[Binding] = &[
+    ("+ / -", "faster / slower"),
+    ("R", "start over"),
+    ("Tab", "next scene"),
+    ("F / F11", "fullscreen"),
+    ("C", "next theme"),
+    ("H", "speed readout"),
+    ("O", "always on top"),
+    ("B", "borderless"),
+    ("X", "print the settings to share"),
+    ("F1 / ?", "this help"),
+    #[cfg(feature = "gui")]
+    ("G", "settings panel"),
+    ("Esc", "close the help or panel, or quit"),
+];
+
+const WATCH_KEYS: &[Binding] = &[
+    ("Space", "pause"),
+    ("Up / Down", "FPS by one"),
+    ("S / Left / Right", "tick or sweep"),
+    ("T", "12 or 24 hours"),
+    ("N", "numerals"),
+    ("D", "date"),
+    ("M", "moon phase"),
+    ("W", "face style"),
+];
+
+const METRONOME_KEYS: &[Binding] = &[
+    ("P", "pause"),
+    ("Space", "tap tempo"),
+    ("[ / ]  Up / Down", "BPM by one"),
+    ("T", "time signature"),
+    ("S", "subdivisions"),
+    ("M", "mute"),
+    ("V", "beat flash"),
+    ("E", "pendulum easing"),
+];
+
+const STOPWATCH_KEYS: &[Binding] = &[
+    ("Space", "start / stop"),
+    ("L", "lap"),
+];
+
+const TIMER_KEYS: &[Binding] = &[
+    ("Space", "start / stop"),
+];
+
+/// The keys for `kind`, its own first. Tab is left out when there's only one scene to show.
+pub fn bindings(kind: SceneKind, scene_count: usize) -> Vec<Binding> {
+    let scene_keys = match kind {
+        SceneKind::Watch => WATCH_KEYS,
+        SceneKind::Metronome => METRONOME_KEYS,
+        SceneKind::Stopwatch => STOPWATCH_KEYS,
+        SceneKind::Timer => TIMER_KEYS,
+        SceneKind::World => &[],
+    };
+    scene_keys
+        .iter()
+        .chain(APP_KEYS.iter().filter(|(key, _)| scene_count > 1 || *key != "Tab"))
+        .copied()
+        .collect()
+}
+
+/// Draw `bindings` in two columns on a panel in the middle of the window and return the box it covers.
+/// The text starts at the speed readout's size and gets smaller until the panel fits the frame.
+pub fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, bindings: &[Binding]) -> Rect {
+    let column_width = |column: fn(&Binding) -> &str, scale| {
+        bindings.iter().map(|binding| font::text_width(column(binding), scale)).max().unwrap_or(0)
+    };
+    // Panel size, text column widths and line height at a text scale
+    let measure = |scale| {
+        let (keys, actions) = (column_width(|&(key, _)| key, scale), column_width(|&(_, action)| action, scale));
+        let (padding, gap) = (font::text_height(scale), font::text_width("   ", scale));
+        let line = font::text_height(scale) * 3 / 2;
+        let size = Dimensions {
+            width: keys + gap + actions + 2 * padding,
+            height: bindings.len() * line + 2 * padding - (line - font::text_height(scale)),
+        };
+        (size, padding, keys + gap, line)
+    };
+    let inset = layout.frame_padding + layout.thick * 2;
+    let room = Dimensions { width: layout.width.saturating_sub(2 * inset), height: layout.height.saturating_sub(2 * inset) };
+    let scale = (1..=layout.hud_scale)
+        .rev()
+        .find(|&scale| {
+            let (size, ..) = measure(scale);
+            size.width <= room.width && size.height <= room.height
+        })
+        .unwrap_or(1);
+    let (size, padding, action_x, line) = measure(scale);
+
+    let top_left = Point::new(
+        layout.width as isize / 2 - size.width as isize / 2,
+        layout.height as isize / 2 - size.height as isize / 2,
+    );
+    canvas.blend_rect(top_left, size, palette.background, PANEL_OPACITY);
+    canvas.draw_rect(top_left, size, scale, palette.foreground);
+    for (i, (key, action)) in bindings.iter().enumerate() {
+        let y = top_left.y + (padding + i * line) as isize;
+        canvas.draw_text(Point::new(top_left.x + padding as isize, y), key, scale, palette.foreground);
+        canvas.draw_text(Point::new(top_left.x + (padding + action_x) as isize, y), action, scale, palette.foreground);
+    }
+
+    // draw_rect's border reaches out half its thickness
+    let end = Point::new(top_left.x + size.width as isize, top_left.y + size.height as isize);
+    Rect::around(&[top_left, end], scale, layout.width, layout.height)
+}
@@ -0,0 +1,89 @@
+// What goes on top of a scene: the on-screen readout of the speed (FPS or BPM) in the top-left
+// corner, toggled with the H key, a scene's notice at the top (like the watch face style just
+// picked), the buttons of controls.rs while the mouse is in use, the settings panel of panel.rs
+// and the key help of help.rs.
+// Each scene draws it last, on top of everything, so a scene that only repaints what changed can
+// treat its boxes like any other moving part.
+
+use watchrs::draw::{self, Canvas, Point, Rect};
+use watchrs::font;
+use crate::controls::{self, Control};
+use crate::help::{self, Binding};
+use crate::layout::Layout;
+#[cfg(feature = "gui")]
+use crate::panel::{self, Row};
+use crate::theme::ThemePalette;
+
+/// The overlays of one frame, each `None` while hidden.
+#[derive(Default)]
+pub struct Hud {
+    pub text:   Option<String>,             // the speed readout
+    pub notice: Option<String>,             // what a key just changed, for a moment (Scene::notice)
+    pub controls: Option<Option<Control>>,  // the buttons, with the one under the cursor
+    #[cfg(feature = "gui")]
+    pub panel:  Option<Vec<Row>>,           // the settings panel
+    pub help:   Option<Vec<Binding>>,       // the keys of the shown scene
+}
+
+impl Hud {
+    /// Draw whatever is shown and return the boxes it covers.
+    pub fn draw(&self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette) -> Vec<Rect> {
+        let mut covered = Vec::new();
+        if let Some(text) = &self.text {
+            covered.push(draw(canvas, layout, palette, text));
+        }
+        if let Some(notice) = &self.notice {
+            covered.push(draw_notice(canvas, layout, palette, notice));
+        }
+        if let Some(hovered) = self.controls {
+            covered.extend(controls::draw(canvas, layout, palette, hovered));
+        }
+        #[cfg(feature = "gui")]
+        if let Some(rows) = &self.panel {
+            covered.push(panel::draw(canvas, layout, palette, rows));
+        }
+        if let Some(bindings) = &self.help {
+            covered.push(help::draw(canvas, layout, palette, bindings));
+        }
+        covered
+    }
+}
+
+/// Draw `text` in the top-left corner inside the frame and return the box it covers.
+///
+/// Black or white, whichever stands out more from the background there, so it stays readable
+/// with every theme and custom color.
+fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, text: &str) -> Rect {
+    let inset = (layout.frame_padding + layout.thick * 2) as isize;
+    let origin = Point::new(inset, inset);
+    canvas.draw_text(origin, text, layout.hud_scale, readable_color(palette));
+
+    let end = Point::new(
+        origin.x + font::text_width(text, layout.hud_scale) as isize,
+        origin.y + font::text_height(layout.hud_scale) as isize,
+    );
+    Rect::around(&[origin, end], 0, layout.width, layout.height)
+}
+
+/// Draw `notice` at twice the readout's size, centered at the top inside the frame, and return the box it covers.
+fn draw_notice(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, notice: &str) -> Rect {
+    let inset = (layout.frame_padding + layout.thick * 2) as isize;
+    let scale = layout.hud_scale * 2;
+    let width = font::text_width(notice, scale) as isize;
+    let origin = Point::new(layout.width as isize / 2 - width / 2, inset);
+    canvas.draw_text(origin, notice, scale, readable_color(palette));
+
+    let end = Point::new(origin.x + width, origin.y + font::text_height(scale) as isize);
+    Rect::around(&[origin, end], 0, layout.width, layout.height)
+}
+
+/// Black or white, whichever stands out more from the background.
+fn readable_color(palette: &ThemePalette) -> u32 {
+    if luminance(palette.background) > 0.5 { draw::color_rgb(0, 0, 0) } else { draw::color_rgb(255, 255, 255) }
+}
+
+/// Perceived brightness of a pixel, 0.0 (black) to 1.0 (white), Rec. 709 weights.
+fn luminance(color: u32) -> f32 {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+    0.2126 * channel(16) + 0.7152 * channel(8) + 0.0722 * channel(0)
+}
@@ -1,24 +1,49 @@
+use std::path::Path;
+
+use log::info;
 use winit::window::Icon;
 
-// Embed the PNG bytes
-const ICON_PNG: &[u8] = include_bytes!("..//resources//icon.png");
+// Embed the PNG bytes, one per size, smallest first.
+// The small ones are downscaled from the 256px artwork, replace them with hand-tuned ones at will.
+const ICON_PNGS: [(u32, &[u8]); 4] = [
+    (16, include_bytes!("..//resources//icon-16.png")),
+    (32, include_bytes!("..//resources//icon-32.png")),
+    (64, include_bytes!("..//resources//icon-64.png")),
+    (256, include_bytes!("..//resources//icon.png")),
+];
+
+/// Size asked for by anything that shows the icon big (taskbars, docks, app switchers).
+pub const LARGE_ICON: u32 = 256;
+/// Size of a title bar icon on a HiDPI screen.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const SMALL_ICON: u32 = 32;
 
+/// The highest quality embedded icon.
 pub fn load_icon_embedded() -> Option<Icon> {
-    match image::load_from_memory(ICON_PNG) {
+    load_icon_embedded_sized(LARGE_ICON)
+}
+
+/// The embedded icon that fits `size` pixels best: the smallest one at least that big,
+/// so it only ever gets scaled down, or the largest one if none is.
+pub fn load_icon_embedded_sized(size: u32) -> Option<Icon> {
+    let (_, png) = ICON_PNGS
+        .iter()
+        .find(|&&(icon_size, _)| icon_size >= size)
+        .unwrap_or(&ICON_PNGS[ICON_PNGS.len() - 1]);
+    match image::load_from_memory(png) {
         Ok(img) => {
             let img = img.into_rgba8();
             let (width, height) = img.dimensions();
             Icon::from_rgba(img.into_raw(), width, height).ok()
         }
         Err(_) => {
-            eprintln!("⚠️  Could not load icon to embed.");
+            info!("Could not load icon to embed.");
             None
         }
     }
 }
 
-#[allow(dead_code)]
-pub fn load_icon(path: &str) -> Option<Icon> {
+pub fn load_icon(path: &Path) -> Option<Icon> {
     match image::open(path) {
         Ok(img) => {
             let img = img.into_rgba8();
@@ -26,8 +51,13 @@ pub fn load_icon(path: &str) -> Option<Icon> {
             Icon::from_rgba(img.into_raw(), width, height).ok()
         }
         Err(_) => {
-            eprintln!("⚠️  Could not load icon file at: '{path}'");
+            info!("Could not load icon file at: '{}'", path.display());
             None
         }
     }
-}
\ No newline at end of file
+}
+
+/// The icon file given with `--icon` if it loads, the highest quality embedded one otherwise.
+pub fn resolve_icon(path: Option<&Path>) -> Option<Icon> {
+    path.and_then(load_icon).or_else(load_icon_embedded)
+}
@@ -0,0 +1,63 @@
+// Pictures for a custom watch face: one for the dial and one per hand, named in the
+// `[face_images]` table of the settings file. Any format the image crate reads, loaded once at
+// startup like the window icon (see icon.rs) and drawn with `Canvas::blit_rotated`.
+//
+// The dial picture is scaled to the dial's diameter and drawn instead of the dial outline, ticks and
+// numerals. A hand picture is drawn pointing at 12 with the pivot in the middle of its bottom edge,
+// scaled so it's as long as the hand would be. Whatever isn't set or doesn't load is drawn as usual.
+
+use std::path::Path;
+
+use log::warn;
+
+use crate::config::{self, FaceImagePaths};
+use watchrs::draw::Image;
+
+/// The loaded pictures, `None` where the face is drawn as usual.
+pub struct FaceImages {
+    pub dial:         Option<Image>,
+    pub hour_hand:    Option<Image>,
+    pub minute_hand:  Option<Image>,
+    pub seconds_hand: Option<Image>,
+}
+
+impl FaceImages {
+    /// Load every picture `paths` names. Relative paths start from the settings file's folder.
+    pub fn load(paths: &FaceImagePaths) -> Self {
+        let load = |path: &Option<_>, pivot: fn(f32, f32) -> (f32, f32)| {
+            path.as_deref().and_then(|path| load_image(path, pivot))
+        };
+        let centered = |width, height| (width / 2.0, height / 2.0);
+        let base = |width, height| (width / 2.0, height);
+        Self {
+            dial: load(&paths.dial, centered),
+            hour_hand: load(&paths.hour_hand, base),
+            minute_hand: load(&paths.minute_hand, base),
+            seconds_hand: load(&paths.seconds_hand, base),
+        }
+    }
+}
+
+/// The picture at `path` with its pivot where `pivot` puts it for its width and height,
+/// `None` (and a warning) if it can't be read.
+fn load_image(path: &Path, pivot: fn(f32, f32) -> (f32, f32)) -> Option<Image> {
+    let path = match config::config_path().as_deref().and_then(Path::parent) {
+        Some(folder) => folder.join(path),
+        None => path.to_path_buf(),
+    };
+    match image::open(&path) {
+        Ok(img) => {
+            let img = img.into_rgba8();
+            let (width, height) = img.dimensions();
+            let pixels = img
+                .pixels()
+                .map(|&image::Rgba([r, g, b, a])| u32::from_be_bytes([a, r, g, b]))
+                .collect();
+            Some(Image { width: width as usize, height: height as usize, pixels, pivot: pivot(width as f32, height as f32) })
+        }
+        Err(e) => {
+            warn!("Could not load face image '{}', drawing that part instead: {e}", path.display());
+            None
+        }
+    }
+}
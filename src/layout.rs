@@ -0,0 +1,211 @@
+use watchrs::draw::{Dimensions, Point};
+
+use crate::config::{DialPosition, HandLengths};
+#[cfg(feature = "world")]
+use watchrs::font;
+
+/// Hub cap radius as a multiple of the hour hand thickness, > 0.5 so it covers the hand bases
+#[cfg(feature = "watch")]
+const HUB_RADIUS_FACTOR: f32 = 1.0;
+
+/// Every size and position that only depends on the window size.
+///
+/// Computed once per resize instead of once per frame, the redraw just reads the fields.
+#[derive(Copy, Clone, Debug)]
+pub struct Layout {
+    pub width:  usize,
+    pub height: usize,
+    pub center: Point,
+    pub thick:  usize,          // line thickness of the frame
+    pub frame_padding: usize,
+    pub hud_scale: usize,       // text scale of the speed readout in the corner
+    #[cfg(feature = "world")]
+    pub px:     usize,          // physical pixels per logical one, the thinnest line and smallest text scale
+    #[cfg(feature = "watch")]
+    pub watch: WatchLayout,
+    #[cfg(feature = "metronome")]
+    pub metronome: MetronomeLayout,
+}
+
+impl Layout {
+    /// `hands` sets the watch hand lengths, the metronome doesn't use it.
+    ///
+    /// Sizes follow the window, which is already measured in physical pixels, so they keep their
+    /// look on any display. `scale_factor` (the window's, 2.0 on a typical HiDPI screen) only
+    /// raises the minimums: a line never gets thinner than one logical pixel, so a small window on
+    /// a HiDPI screen doesn't end up with hairlines.
+    ///
+    /// Everything is drawn in a square as big as the shorter side, `position` places it along the longer one.
+    pub fn new(width: usize, height: usize, hands: HandLengths, scale_factor: f64, position: DialPosition) -> Self {
+        let min_dim = width.min(height);
+        // On the shorter side the square fills it, all three positions give its middle
+        let along = |length: usize| {
+            let offset = match position {
+                DialPosition::Start => 0,
+                DialPosition::Center => (length - min_dim) / 2,
+                DialPosition::End => length - min_dim,
+            };
+            (offset + min_dim / 2) as isize
+        };
+        let center = Point::new(along(width), along(height));
+        let px = (scale_factor.round() as usize).max(1);
+        Self::around(width, height, center, min_dim, hands, px)
+    }
+
+    /// Sizes for a drawing `min_dim` pixels across around `center`, on a `width` x `height` canvas.
+    #[cfg_attr(not(feature = "watch"), allow(unused_variables))]
+    fn around(width: usize, height: usize, center: Point, min_dim: usize, hands: HandLengths, px: usize) -> Self {
+        let thick = ((min_dim as f32 * 0.03).round() as usize).max(px);
+        let frame_padding = ((min_dim as f32 * 0.04).round() as usize).max(px);
+
+        Self {
+            width,
+            height,
+            center,
+            thick,
+            frame_padding,
+            hud_scale: (min_dim / 300).max(px),
+            #[cfg(feature = "world")]
+            px,
+            #[cfg(feature = "watch")]
+            watch: WatchLayout::new(min_dim, center, thick, frame_padding, hands, px),
+            #[cfg(feature = "metronome")]
+            metronome: MetronomeLayout::new(width, height, center, frame_padding),
+        }
+    }
+
+    /// The window inside the frame split into `count` cells, `columns` per row, one layout per cell.
+    ///
+    /// Each cell layout still covers the whole window (so dirty rectangles clip to the canvas),
+    /// but its center and watch sizes come from the cell: the dial fits the cell minus a line for
+    /// the label under it, so the hands never leave their cell. A short last row is centered.
+    #[cfg(feature = "world")]
+    pub fn grid(&self, count: usize, columns: usize) -> Vec<WorldCell> {
+        if count == 0 {
+            return Vec::new();
+        }
+        let columns = columns.clamp(1, count);
+        let rows = count.div_ceil(columns);
+        let inset = self.frame_padding + self.thick * 2;
+        let cell_width = self.width.saturating_sub(inset * 2) / columns;
+        let cell_height = self.height.saturating_sub(inset * 2) / rows;
+
+        // The label line takes the bottom of the cell, the dial the square that's left above it
+        let label_scale = (cell_width.min(cell_height) / 150).max(self.px);
+        let label_height = font::text_height(label_scale) * 2;
+        let min_dim = cell_width.min(cell_height.saturating_sub(label_height));
+
+        (0..count)
+            .map(|i| {
+                let (row, column) = (i / columns, i % columns);
+                // Cells missing from the last row shift it right by half a cell each
+                let in_row = columns.min(count - row * columns);
+                let row_offset = (columns - in_row) * cell_width / 2;
+                let left = inset + row_offset + column * cell_width;
+                let top = inset + row * cell_height;
+                let dial_height = cell_height.saturating_sub(label_height);
+                let center = Point::new((left + cell_width / 2) as isize, (top + dial_height / 2) as isize);
+                WorldCell {
+                    layout: Self::around(self.width, self.height, center, min_dim, self.watch.hand_lengths, self.px),
+                    label_center: Point::new(center.x, (top + dial_height + label_height / 4) as isize),
+                    label_scale,
+                    label_width: cell_width,
+                }
+            })
+            .collect()
+    }
+
+    /// Canvas size matching this layout.
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions { width: self.width, height: self.height }
+    }
+}
+
+/// One clock of the world clock grid, see `Layout::grid`.
+#[cfg(feature = "world")]
+#[derive(Copy, Clone, Debug)]
+pub struct WorldCell {
+    pub layout: Layout,         // centered on the cell's dial, sized to fit it
+    pub label_center: Point,    // top middle of the label under the dial
+    pub label_scale: usize,     // largest text scale for the label, smaller if the name is long
+    pub label_width: usize,     // room the label has, the width of the cell
+}
+
+#[cfg(feature = "watch")]
+#[derive(Copy, Clone, Debug)]
+pub struct WatchLayout {
+    pub dial_radius: usize,
+    pub dial_thick: usize,
+    pub minute_tick: (usize, usize),    // (length, thickness)
+    pub hour_tick: (usize, usize),
+    pub numeral_scale: usize,           // largest text scale of the hour labels, long ones get smaller
+    pub readout_scale: usize,
+    pub readout_y: isize,               // top of the digital readout
+    pub hour_hand: (f32, usize),        // (length, thickness)
+    pub minute_hand: (f32, usize),
+    pub seconds_hand: (f32, usize),
+    pub hub_radius: usize,
+    #[cfg(feature = "world")]
+    pub hand_lengths: HandLengths,      // the ratios the hand lengths come from, for the grid cells
+}
+
+#[cfg(feature = "watch")]
+impl WatchLayout {
+    fn new(min_dim: usize, center: Point, thick: usize, frame_padding: usize, hands: HandLengths, px: usize) -> Self {
+        // Radius of the round dial inside the frame
+        // This saturating_sub() prevents the number usize from becoming negative
+        // if it was isize, then it is: ((min_dim / 2)-(frame_padding * 2)).max(0);
+        let dial_radius = (min_dim / 2).saturating_sub(frame_padding * 2);
+        let hour_tick_length = (min_dim as f32 * 0.06).round() as usize;
+
+        Self {
+            dial_radius,
+            dial_thick: (thick / 4).max(px),
+            minute_tick: ((min_dim as f32 * 0.025).round() as usize, (thick / 4).max(px)),
+            hour_tick: (hour_tick_length, (thick * 2 / 3).max(px)),
+            numeral_scale: (min_dim / 110).max(px),
+            readout_scale: (min_dim / 150).max(px),
+            readout_y: center.y + (dial_radius as f32 * 0.35).round() as isize,
+            // Lengths from the settings, at most the dial radius so the tips stay inside the dial.
+            // The hour hand is the thickest, the seconds hand the thinnest
+            hour_hand: (dial_radius as f32 * hands.hour.clamp(0.0, 1.0), thick),
+            minute_hand: (dial_radius as f32 * hands.minute.clamp(0.0, 1.0), (thick * 2 / 3).max(px)),
+            seconds_hand: (dial_radius as f32 * hands.seconds.clamp(0.0, 1.0), (thick / 3).max(px)),
+            hub_radius: (thick as f32 * HUB_RADIUS_FACTOR).round().max(px as f32) as usize,
+            #[cfg(feature = "world")]
+            hand_lengths: hands,
+        }
+    }
+}
+
+#[cfg(feature = "metronome")]
+#[derive(Copy, Clone, Debug)]
+pub struct MetronomeLayout {
+    pub triangle: [Point; 3],   // top, bottom left, bottom right
+    pub hand_length: usize,
+    pub hanging_length: usize,  // length of an arm hanging from the apex, two thirds of the way down or less
+    pub count_in_scale: usize,  // text scale of the count-in numbers
+}
+
+#[cfg(feature = "metronome")]
+impl MetronomeLayout {
+    fn new(width: usize, height: usize, center: Point, frame_padding: usize) -> Self {
+        let min_dim = width.min(height);
+        let bottom = height.saturating_sub(frame_padding * 2) as isize;
+        // The base reaches as far to either side as the nearer window edge allows,
+        // the whole width when centered, less when the drawing sits to one side
+        let half_base = center.x.min(width as isize - center.x) - (frame_padding * 4) as isize;
+        Self {
+            triangle: [
+                Point::new(center.x, (frame_padding * 2) as isize),
+                Point::new(center.x - half_base, bottom),
+                Point::new(center.x + half_base, bottom),
+            ],
+            hand_length: (min_dim / 2).saturating_sub(frame_padding * 2),
+            // Short enough that swung out level (a 90° arc) it stays inside the frame
+            hanging_length: ((bottom as usize).saturating_sub(frame_padding * 2) * 2 / 3)
+                .min((half_base + (frame_padding * 2) as isize).max(0) as usize),
+            count_in_scale: (min_dim / 40).max(1),
+        }
+    }
+}
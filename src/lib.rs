@@ -0,0 +1,8 @@
+//! Software rendering primitives of WATCHRS, usable without a window.
+//!
+//! `draw` paints into a plain `&mut [u32]` pixel buffer (0x00RRGGBB, the softbuffer format),
+//! `font` is the small bitmap font `Canvas::draw_text` uses.
+//! The clock and metronome binary in `main.rs` is built on top of these.
+
+pub mod draw;
+pub mod font;
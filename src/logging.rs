@@ -0,0 +1,42 @@
+// Where the log macros used across the app end up.
+//
+// With the "logging" feature env_logger prints them, filtered by RUST_LOG: info shows what the
+// keys did, debug adds every key press and speed change. Unset, it prints warnings and errors only.
+// Without the feature, warnings and errors go to stderr like they always did and the rest is
+// dropped, so a normal build stays quiet.
+
+#[cfg(not(feature = "logging"))]
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Install the logger, before anything that may log (loading the settings, parsing the flags).
+#[cfg(feature = "logging")]
+pub fn init() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+}
+
+/// Install the logger, before anything that may log (loading the settings, parsing the flags).
+#[cfg(not(feature = "logging"))]
+pub fn init() {
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(LevelFilter::Warn);
+    }
+}
+
+/// Warnings and errors to stderr, marked the way the app always marked them.
+#[cfg(not(feature = "logging"))]
+struct StderrLogger;
+
+#[cfg(not(feature = "logging"))]
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("⚠️  {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
@@ -1,50 +1,94 @@
 #![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
 
-use std::num::NonZeroU32;
-use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::time::Instant;
 
 
+use log::{debug, error, info, warn};
 use winit::{
     application::ApplicationHandler,
-    event::{WindowEvent, ElementState},
-    event_loop::{ActiveEventLoop, EventLoop,ControlFlow},
-    window::{Window, WindowId},
+    event::{WindowEvent, ElementState, MouseButton, MouseScrollDelta},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    monitor::MonitorHandle,
+    window::{Fullscreen, Icon, Window, WindowAttributes, WindowId, WindowLevel},
     dpi::PhysicalSize,
     keyboard::{Key, NamedKey},
 };
 
 
-use softbuffer;
+use watchrs::draw;
 
+use crate::error::AppError;
 
+mod error;
 mod icon;
-mod draw;
+mod layout;
+mod logging;
+mod timing;
+mod vsync;
+mod window;
+mod config;
+mod controls;
+mod help;
+mod hud;
+mod render;
+mod scene;
+mod theme;
+mod time_source;
+mod cli;
+mod share;
+#[cfg(feature = "hot-reload")]
+mod reload;
+#[cfg(feature = "battery-saver")]
+mod power;
+#[cfg(feature = "gui")]
+mod panel;
+#[cfg(feature = "ntp")]
+mod ntp;
+// The watch only rings bells, the clicks are the metronome's and the timer's
+#[cfg_attr(not(any(feature = "metronome", feature = "timer")), allow(dead_code))]
+mod audio;
+#[cfg(feature = "watch")]
+mod chime;
+#[cfg(feature = "metronome")]
+mod pendulum;
+#[cfg(feature = "metronome")]
+mod metronome;
+#[cfg(feature = "watch")]
+mod clock;
+#[cfg(feature = "watch")]
+mod complications;
+#[cfg(feature = "watch")]
+mod images;
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "stopwatch")]
+mod stopwatch;
+#[cfg(feature = "timer")]
+mod timer;
+#[cfg(feature = "world")]
+mod world;
 
-const APP_NAME: &str = "WATCHRS - Analog Clock";
 const WIDTH: usize = 1200;
 const HEIGHT: usize = 900;
-const COLOR_BACKGROUND: u32 = draw::color_rgb(75, 95, 100);
-
-
-
-cfg_if::cfg_if! {
-    if #[cfg(feature = "watch")] {
-        const MAX_FPS: u32 = 20; 
-        // Code specific to the "watch" feature
-        const COLOR_1: u32 = draw::color_rgb(0, 200, 255);
-        const MIN_FPS: u32 = 1;
-    } else if #[cfg(feature = "metronome")] {
-        // Code specific to the "metronome" feature
-        const COLOR_1: u32 = draw::color_rgb(0, 255, 30);
-        const MAX_BPM: u32 = 200;
-        const MIN_BPM: u32 = 20;
-        const SWING_ARC: f32 = 60.0;
-        const MIN_FPS: u32 = 60;
-    } else {
-        compile_error!("Either feature \"watch\" or \"metronome\" must be enabled.");
-    }
-}
+
+
+
+// Either scene or both ("combined", switched with Tab)
+#[cfg(feature = "watch")]
+use watch::{MAX_FPS, MIN_FPS};
+#[cfg(feature = "metronome")]
+use metronome::{MAX_BPM, MIN_BPM};
+#[cfg(feature = "timer")]
+use timer::{MAX_TIMER, MIN_TIMER};
+#[cfg(not(any(feature = "watch", feature = "metronome")))]
+compile_error!("Feature \"watch\", \"metronome\" or both (\"combined\") must be enabled.");
+
+// Frames in a row that may fail to show before the app gives up and exits
+const MAX_FAILED_FRAMES: u32 = 30;
+
+// Trackpads scroll in pixels, this many count as one notch of a mouse wheel
+const PIXELS_PER_SCROLL_STEP: f64 = 40.0;
 
 // #[cfg(feature = "watch")]
 // const COLOR_1: u32 = draw::color_rgb(0, 200, 255);
@@ -61,243 +105,550 @@ cfg_if::cfg_if! {
 
 
 struct App {
-    window:     Option<Rc<Window>>,
-    surface:    Option<softbuffer::Surface<Rc<Window>, Rc<Window>>>,
-    start:      Instant,
-    fps:        u32,
-    next_frame: Instant,
-    #[cfg(feature = "metronome")]
-    bpm:        u32,
+    windows:    HashMap<WindowId, window::WindowState>, // the open windows, the app exits with the last one
+    icon:       Option<Icon>,   // resolved once at startup, from --icon or the embedded one
+    #[cfg(windows)]
+    small_icon: Option<Icon>,   // title bar icon, None to use `icon` there too
+    settings:   config::Settings,
+    frame_timer: Option<timing::FrameTimer>,    // only with --stats, one for all the windows
+    #[cfg(feature = "hot-reload")]
+    _settings_watcher: Option<notify::RecommendedWatcher>, // watches the settings file while it lives
+    #[cfg(feature = "battery-saver")]
+    power:      Option<power::PowerProbe>,  // only with the battery saver on
+    args:       cli::Args,      // the scenes of every window are set up from them, and --once and --per-monitor
+    error:      Option<AppError>,   // what stopped the app, reported once the event loop returns
 }
 
-impl ApplicationHandler<()> for App {
-    // We’ll add window creation here in the next step.
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-
-        // Create a window
+impl App {
+    /// Attributes for a window that opens showing a `kind` scene, fullscreen on `monitor` if one is given.
+    fn window_attributes(&self, kind: scene::SceneKind, monitor: Option<MonitorHandle>) -> WindowAttributes {
         let attrs = Window::default_attributes()
-            .with_title(APP_NAME)
-            .with_window_icon(icon::load_icon_embedded())
+            .with_title(kind.title())
+            .with_window_icon(self.icon.clone())
             .with_inner_size(PhysicalSize::new(WIDTH as u32, HEIGHT as u32))
             .with_resizable(true)
+            .with_window_level(window_level(self.settings.always_on_top))
+            .with_decorations(!self.settings.borderless)
+            .with_transparent(self.settings.transparent)
             ;
+        // Placed on the monitor as well, so it opens there on platforms that show it before going fullscreen
+        let attrs = match monitor {
+            Some(monitor) => attrs.with_position(monitor.position()).with_fullscreen(Some(Fullscreen::Borderless(Some(monitor)))),
+            None => attrs,
+        };
+        // Windows shows the window icon small (title bar, alt-tab) and takes a separate big one for
+        // the taskbar, everywhere else the one icon gets scaled to whatever size is needed
+        #[cfg(windows)]
+        let attrs = {
+            use winit::platform::windows::WindowAttributesExtWindows;
+            attrs
+                .with_window_icon(self.small_icon.clone().or_else(|| self.icon.clone()))
+                .with_taskbar_icon(self.icon.clone())
+        };
+        attrs
+    }
+
+    /// Keep the windows above the others or let them go behind them again.
+    /// Platforms without window levels (Wayland, for one) ignore it, the setting is still remembered.
+    fn toggle_always_on_top(&mut self) {
+        self.settings.always_on_top = !self.settings.always_on_top;
+        for win in self.windows.values() {
+            win.window.set_window_level(window_level(self.settings.always_on_top));
+        }
+        info!("Always on top {}", if self.settings.always_on_top { "on" } else { "off" });
+    }
 
-        // With an Rc we 'own' the window and hand owned handles to softbuffer
-        let window = Rc::new(event_loop.create_window(attrs).unwrap());
-        let context = softbuffer::Context::new(window.clone()).unwrap();
-        let mut surface = softbuffer::Surface::new(&context, window.clone()).unwrap();
+    /// Hide the title bars and borders or bring them back.
+    /// The windows change size with them, the surfaces follow through the Resized events.
+    fn toggle_borderless(&mut self) {
+        self.settings.borderless = !self.settings.borderless;
+        for win in self.windows.values() {
+            win.window.set_decorations(!self.settings.borderless);
+        }
+        info!("Borderless {}", if self.settings.borderless { "on" } else { "off" });
+    }
 
+    /// Tell the scenes when the computer went on battery or back on AC power, with the battery saver on.
+    #[cfg(feature = "battery-saver")]
+    fn check_power(&mut self) {
+        let Some(on_battery) = self.power.as_mut().and_then(|power| power.poll(Instant::now())) else {
+            return;
+        };
+        for win in self.windows.values_mut() {
+            win.set_on_battery(on_battery);
+        }
+    }
 
-        // resize the surface to the actual inner_size (PHYSICAL)
-        let window_size = window.inner_size(); 
-        surface
-            .resize(
-                NonZeroU32::new(window_size.width).unwrap(),
-                NonZeroU32::new(window_size.height).unwrap(),
-            )
-            .unwrap();
+    /// Switch to the next preset theme, every window fading into it from the colors it shows now
+    /// (part of the way into another theme, if it's pressed again mid-fade).
+    fn cycle_theme(&mut self) {
+        let settings = &mut self.settings;
+        settings.theme = settings.theme.next();
+        for win in self.windows.values_mut() {
+            win.fade_to(settings);
+        }
+        info!("Theme: {:?}", settings.theme);
+        if settings.background.is_some() || settings.foreground.is_some() || settings.seconds_hand.is_some() {
+            warn!("Colors set in the settings file still override the theme");
+        }
+    }
 
-        self.window = Some(window);
-        self.surface = Some(surface);
-        self.next_frame = Instant::now();
+    /// Hand what the settings panel wrote into the settings to every window and its scenes.
+    #[cfg(feature = "gui")]
+    fn apply_settings(&mut self) {
+        for win in self.windows.values_mut() {
+            win.apply_settings(&self.settings);
+        }
     }
 
-    #[cfg(feature = "watch")]
-    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        {
-            let fps = self.fps.clamp(MIN_FPS, MAX_FPS);
-            let now = Instant::now();
-            
-            // Are we at/after the scheduled time?
-            if now >= self.next_frame {
-                // 1) Request exactly one redraw for this tick
-                if let Some(w) = &self.window {
-                    w.request_redraw();
+    /// Take the look from the settings file after it changed: the theme, the colors, the hand
+    /// lengths and the dial position. The rest (speeds, options toggled by keys) is the running app's now and is
+    /// written back on exit.
+    #[cfg(feature = "hot-reload")]
+    fn reload_settings(&mut self) {
+        let Some(reloaded) = config::Settings::reload() else {
+            return;
+        };
+        let settings = &mut self.settings;
+        settings.theme = reloaded.theme;
+        settings.background = reloaded.background;
+        settings.foreground = reloaded.foreground;
+        settings.background_gradient = reloaded.background_gradient;
+        settings.seconds_hand = reloaded.seconds_hand;
+        settings.hand_lengths = reloaded.hand_lengths.clamped();
+        settings.dial_position = reloaded.dial_position;
+        for win in self.windows.values_mut() {
+            win.palette = settings.palette(win.scene().kind());
+            win.relayout(win.layout.width, win.layout.height, settings);
+            win.window.request_redraw();
+        }
+        info!("Reloaded the settings file");
+    }
+
+    /// Close the window `id`, after fading out if the settings ask for it.
+    /// Asking again while it fades closes it right away.
+    fn close(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        let Some(win) = self.windows.get_mut(&id) else {
+            return;
+        };
+        if !(self.settings.fade_on_close && win.start_closing()) {
+            self.remove_window(event_loop, id);
+        }
+    }
+
+    /// Close the window `id` now, and the app with its last window.
+    /// Its scenes hand their speeds and options to the settings first, so the window closed
+    /// last has the last word on what's saved.
+    fn remove_window(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        // Dropping the state drops the window (and the surface, the last other handle to it)
+        if let Some(win) = self.windows.remove(&id) {
+            win.save(&mut self.settings);
+        }
+        if self.windows.is_empty() {
+            event_loop.exit();
+        }
+    }
+
+    /// Act on a key pressed in the window `id` (help.rs lists them all). Keys for the settings the
+    /// windows share (theme, speed readout, window level, borders) change every window, the others
+    /// only the one with the key.
+    fn key(&mut self, event_loop: &ActiveEventLoop, id: WindowId, key: &Key) {
+        let Some(win) = self.windows.get_mut(&id) else {
+            return;
+        };
+        // Space is tap tempo in the metronome, so it pauses with P instead
+        let kind = win.scene().kind();
+        match key {
+            Key::Named(NamedKey::Space) if kind == scene::SceneKind::Watch => win.toggle_pause(),
+            Key::Character(s) if s == "p" && kind == scene::SceneKind::Metronome => win.toggle_pause(),
+            Key::Named(NamedKey::F11) => win.toggle_fullscreen(),
+            Key::Character(s) if s == "f" => win.toggle_fullscreen(),
+            Key::Character(s) if s == "c" => self.cycle_theme(),
+            Key::Character(s) if s == "o" => self.toggle_always_on_top(),
+            Key::Character(s) if s == "b" => self.toggle_borderless(),
+            Key::Character(s) if s == "h" => {
+                self.settings.show_hud = !self.settings.show_hud;
+                info!("Speed readout {}", if self.settings.show_hud { "on" } else { "off" });
+            }
+            // shift+'=' on many keyboards; this catches the "+" character
+            Key::Character(s) if s == "+" => win.scene().change_speed(1),
+            Key::Character(s) if s == "-" => win.scene().change_speed(-1),
+            Key::Named(NamedKey::Tab) => win.switch_scene(&self.settings),
+            Key::Character(s) if s == "r" => win.reset_scene(),
+            Key::Character(s) if s == "x" => {
+                // The settings this window shows, its scenes' own on top of the shared ones
+                let mut settings = self.settings.clone();
+                win.save(&mut settings);
+                println!("{}", share::export(&settings));
+                info!("Printed the settings as a config string, --config-string <STRING> starts with them");
+            }
+            Key::Named(NamedKey::F1) => win.show_help = !win.show_help,
+            Key::Character(s) if s == "?" => win.show_help = !win.show_help,
+            #[cfg(feature = "gui")]
+            Key::Character(s) if s == "g" => win.toggle_panel(),
+            // Closes the help or the panel first, if one is open
+            Key::Named(NamedKey::Escape) if win.show_help => win.show_help = false,
+            #[cfg(feature = "gui")]
+            Key::Named(NamedKey::Escape) if win.show_panel => win.toggle_panel(),
+            Key::Named(NamedKey::Escape) => self.close(event_loop, id),
+            key => win.scene().key(key),
+        }
+        // Show what the key changed now rather than at the next tick, which can be a second away,
+        // and pace the frames after it anew: switching to a sweeping hand needs its frame rate now
+        let now = Instant::now();
+        for win in self.windows.values_mut() {
+            win.next_frame = now;
+            win.window.request_redraw();
+        }
+    }
+}
+
+impl ApplicationHandler<()> for App {
+    // Opens the window, or with --per-monitor one fullscreen window on each monitor
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // Platforms that suspend the app (Android) resume it again, its windows are still open then
+        if !self.windows.is_empty() {
+            return;
+        }
+        let monitors: Vec<MonitorHandle> = match self.args.per_monitor {
+            true => event_loop.available_monitors().collect(),
+            false => Vec::new(),
+        };
+        if self.args.per_monitor {
+            match monitors.len() {
+                0 => warn!("No monitors found, opening a single window"),
+                count => info!("Opening a window on each of {count} monitors"),
+            }
+        }
+        // None opens a window wherever the OS puts it
+        let placements: Vec<Option<MonitorHandle>> = match monitors.is_empty() {
+            true => vec![None],
+            false => monitors.into_iter().map(Some).collect(),
+        };
+
+        for (i, monitor) in placements.into_iter().enumerate() {
+            // One click per beat is enough, only the first window's scenes make sound
+            let scenes = build_scenes(&self.args, &self.settings, i == 0);
+            let attrs = self.window_attributes(scenes[0].kind(), monitor);
+            match window::WindowState::open(event_loop, attrs, scenes, &self.settings) {
+                Ok(win) => {
+                    self.windows.insert(win.window.id(), win);
                 }
-                
-                let frame_duration = Duration::from_secs_f32(1.0 / fps as f32);
-                // self.next_frame += frame_duration;
-                // self.next_frame = self.start + self.start.elapsed() + frame_duration;
-                loop {
-                    self.next_frame += frame_duration;
-                    if self.next_frame > now { break; }
+                Err(e) => {
+                    self.error = Some(e);
+                    event_loop.exit();
+                    return;
                 }
             }
-            event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
         }
     }
 
-    #[cfg(feature = "metronome")]
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(w) = &self.window {
-            w.request_redraw();
+    // The settings file changed (see reload.rs)
+    #[cfg(feature = "hot-reload")]
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, _event: ()) {
+        self.reload_settings();
+    }
+
+    // Called once when the event loop shuts down (the last window closed)
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Remember the current speed and options for the next launch
+        for win in self.windows.values() {
+            win.save(&mut self.settings);
+        }
+        self.settings.save();
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Checked on the next wake-up after it's due, at least once a second with the watch
+        #[cfg(feature = "battery-saver")]
+        self.check_power();
+
+        // Windows done fading out go now
+        let faded: Vec<WindowId> = self.windows.iter().filter(|(_, win)| win.faded_out()).map(|(&id, _)| id).collect();
+        for id in faded {
+            self.remove_window(event_loop, id);
         }
+
+        // Each window asks for the frames it's due and says when it needs waking again, the
+        // earliest of those wakes the loop. Waiting for events only if none has a schedule.
+        let now = Instant::now();
+        let wake = self.windows.values_mut().filter_map(|win| win.schedule(now)).min();
+        event_loop.set_control_flow(wake.map_or(ControlFlow::Wait, ControlFlow::WaitUntil));
     }
 
     fn window_event(
             &mut self,
-            event_loop: &ActiveEventLoop,   
-            _id: WindowId,
+            event_loop: &ActiveEventLoop,
+            id: WindowId,
             event: WindowEvent,
         ) {
+        // Events can still come in for a window closed a moment ago
+        let Some(win) = self.windows.get_mut(&id) else {
+            return;
+        };
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
-
-            WindowEvent::Resized(new_size) => {
-                let surface = self.surface.as_mut().unwrap();
-                surface.resize(
-                    NonZeroU32::new(new_size.width).unwrap(),
-                    NonZeroU32::new(new_size.height).unwrap()). 
-                    unwrap();
-            }
-
-            WindowEvent::RedrawRequested => {
-                use draw::Point;
+            WindowEvent::CloseRequested => self.close(event_loop, id),
 
-                let window = self.window.as_ref().unwrap();
-                let window_size = window.inner_size();
-                
-                // Acquire the frame
-                let surface = self.surface.as_mut().unwrap();
-                let canvas_size = draw::Dimensions { width: window_size.width as usize, height: window_size.height as usize };
-                let mut canvas_buffer = surface.buffer_mut().unwrap();
+            // The window may have landed on a monitor with another refresh rate
+            WindowEvent::Moved(_) => win.update_refresh_interval(),
 
-                let mut canvas = draw::Canvas::new(&mut canvas_buffer, canvas_size);
+            // Moved to a monitor with another DPI (or the setting changed): another refresh rate maybe,
+            // and other minimum line widths. The window gets a new physical size with it, which comes
+            // as a Resized event right after and resizes the surface like any other. Relayout now
+            // anyway, in case the size stays the same (only the line widths change then).
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => win.rescale(scale_factor, &self.settings),
 
-                canvas.clear(COLOR_BACKGROUND);
+            WindowEvent::Resized(new_size) => {
+                // Minimizing or dragging an edge all the way in can report a 0 width/height,
+                // which softbuffer can't resize to. Keep the old surface until a real size arrives.
+                let Some((width, height)) = window::clamp_surface_size(new_size.width, new_size.height) else {
+                    return;
+                };
+                // Dragging an edge sends a burst of these, often several per frame. Only the layout
+                // follows each one, the surface is resized once for the latest size when the frame is
+                // drawn. The redraw is requested right away so the face tracks the drag instead of
+                // waiting for the next tick or for the OS to ask.
+                win.resize(width, height, &self.settings);
+            }
 
-                #[cfg(feature = "watch")]
-                {
-                    let thick = (canvas.min_dim() as f32 * 0.03).max(1.0).round() as usize;
-                    let frame_padding = (canvas.min_dim() as f32 * 0.04).max(1.0).round() as usize;
-                    canvas.draw_frame(frame_padding, thick, COLOR_1);
-                    
-                    // Clock hand geometry
-                    // This saturating_sub() prevents the number usize from becoming negative
-                    // if it was isize, then it is: ((canvas.min_dim() / 2)-(frame_padding * 2)).max(0);
-                    let seconds_hand_length = (canvas.min_dim() / 2).saturating_sub(frame_padding * 2);
-                    
-                    // Tip of the hand
-                    let seconds_since_start = self.start.elapsed().as_secs_f32() % 60.0;
-                    let ticks = (seconds_since_start* self.fps as f32).round() / self.fps as f32; 
-                    let angle = -std::f32::consts::FRAC_PI_2 + ticks * (std::f32::consts::TAU / 60.0);
-                    
-                    let center = canvas.center();
-                    let seconds_hand_tip = Point::new(
-                        center.x + (angle.cos() * seconds_hand_length as f32).round() as isize,
-                        center.y + (angle.sin() * seconds_hand_length as f32).round() as isize,
-                    );
-
-                    // Draw the hand
-                    canvas.draw_line(center, seconds_hand_tip,thick, COLOR_1);
+            // A frame that fails to show is skipped and the next one tried, only a run of them
+            // gives up: a failure mid-resize or while the display reconfigures usually passes
+            WindowEvent::RedrawRequested => match win.redraw(&mut self.settings, self.frame_timer.as_mut()) {
+                Ok(shown) => {
+                    win.failed_frames = 0;
+                    // --once: that frame was all there is to show
+                    if shown && self.args.once {
+                        event_loop.exit();
+                    }
                 }
-
-                #[cfg(feature = "metronome")]
-                {
-                    let thick = (canvas.min_dim() as f32 * 0.03).max(1.0).round() as usize;
-                    let frame_padding = (canvas.min_dim() as f32 * 0.04).max(1.0).round() as usize;
-                    canvas.draw_frame(frame_padding, thick, COLOR_1);
-
-                    // Draw triangle
-                    let top_point = Point::new(
-                        canvas.center().x,
-                        (frame_padding*2) as isize
-                    );
-                    let left_point = Point::new(
-                        (frame_padding*4) as isize,
-                        (canvas.height() - frame_padding*2) as isize
-                    );
-                    let right_point = Point::new(
-                        (canvas.width() - frame_padding*4) as isize,
-                        (canvas.height() - frame_padding*2) as isize
-                    );
-                    
-                    canvas.draw_line(top_point, left_point, thick, COLOR_1);
-                    canvas.draw_line(top_point, right_point, thick, COLOR_1);
-                    canvas.draw_line(right_point, left_point, thick, COLOR_1);
-
-                    let hand_length = (canvas.min_dim() / 2).saturating_sub(frame_padding * 2);
-                    let angle_left = std::f32::consts::PI * 5.0 / 6.0; // 10 o'clock
-                    let angle_right = std::f32::consts::PI * 1.0 / 6.0; // 2 o'clock
-                    // let top_angle = std::f32::consts::FRAC_PI_2; 
-
-                    let beats_per_sec = self.bpm as f32 / 60.0;
-                    let beat_interval = 60.0 / (self.bpm as f32); 
-                    let elapsed = self.start.elapsed().as_secs_f32();
-                    let phase = (elapsed * beats_per_sec) % 1.0; // 0..1
-                    let hand_angle = angle_left + (angle_right - angle_left) * phase;
-                    
-                    let swing = (std::f32::consts::PI * (elapsed / beat_interval)).cos();
-                    let up = -std::f32::consts::FRAC_PI_2;            // UP is -90°
-                    let max_swing_rad = SWING_ARC.to_radians();
-                    let hand_angle = up + swing * max_swing_rad;
-
-                    let hand_tip = Point::new(
-                        canvas.center().x + (hand_angle.cos() * hand_length as f32).round() as isize,
-                        canvas.center().y + (hand_angle.sin() * hand_length as f32).round() as isize,
-                    );
-                    canvas.draw_line(canvas.center(), hand_tip, thick, COLOR_1);
+                Err(e) => {
+                    win.failed_frames += 1;
+                    if win.failed_frames >= MAX_FAILED_FRAMES {
+                        self.error = Some(e);
+                        event_loop.exit();
+                    } else {
+                        warn!("Skipping frame: {e}");
+                        win.window.request_redraw();
+                    }
+                }
+            },
+
+            WindowEvent::CursorMoved { position, .. } => {
+                let layout = win.layout;
+                let (previous, was_shown) = (win.cursor, win.controls_shown());
+                win.cursor = draw::Point::new(
+                    (position.x as isize).clamp(0, layout.width as isize - 1),
+                    (position.y as isize).clamp(0, layout.height as isize - 1),
+                );
+                win.cursor_moved_at = Some(Instant::now());
+                // A held panel slider follows the cursor, wherever it goes
+                #[cfg(feature = "gui")]
+                if let Some(field) = win.slider {
+                    let kind = win.scene().kind();
+                    panel::drag(&layout, &mut self.settings, kind, field, win.cursor);
+                    self.apply_settings();
+                    return;
                 }
+                // Redraw for the buttons showing up or another one lighting up, or for the scene
+                let controls_changed = !was_shown || controls::hit(&layout, previous) != controls::hit(&layout, win.cursor);
+                let cursor = win.cursor;
+                if win.scene().cursor_moved(&layout, cursor) || controls_changed {
+                    win.window.request_redraw();
+                }
+            }
 
-                window.pre_present_notify();
-                canvas_buffer.present().unwrap();
+            WindowEvent::CursorLeft { .. } => {
+                win.cursor_moved_at = None;
+                win.window.request_redraw();
             }
 
-            WindowEvent::KeyboardInput { event: key_event, .. } => {
-                if key_event.state == ElementState::Pressed {
-
-                    eprintln!(
-                        "Key pressed: logical={:?}, text={:?}",
-                        key_event.logical_key,
-                        key_event.text
-                    );
-                    #[cfg(feature = "watch")]
-                    match &key_event.logical_key {
-                        Key::Character(s) if s == "+" => {
-                            // shift+'=' on many keyboards; this catches the "+" character
-                            self.fps = (self.fps + 2).min(MAX_FPS);
-                            eprintln!("TPS increased to: {}", self.fps);
-                        }
-                        Key::Character(s) if s == "-" => {
-                            self.fps = self.fps.saturating_sub(2).max(MIN_FPS);
-                            eprintln!("TPS reduced to:: {}", self.fps);
-                        }
-                        Key::Named(NamedKey::Escape) => event_loop.exit(),
-                        _ => {}
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                // A press on a shown button (or the settings panel) is for it, not for the scene underneath
+                let pressed = state == ElementState::Pressed;
+                #[cfg(feature = "gui")]
+                {
+                    if !pressed && win.slider.take().is_some() {
+                        return;
                     }
-                    #[cfg(feature = "metronome")]
-                    match &key_event.logical_key {
-                        Key::Character(s) if s == "+" => {
-                            self.bpm = (self.bpm + 5).min(MAX_BPM);
-                            eprintln!("BPM increased to: {}", self.bpm);
+                    if pressed && let Some(changed) = win.press_panel(&mut self.settings) {
+                        if changed {
+                            self.apply_settings();
                         }
-                        Key::Character(s) if s == "-" => {
-                            self.bpm = self.bpm.saturating_sub(5).max(MIN_BPM);
-                            eprintln!("BPM reduced to: {}", self.bpm);
+                        return;
+                    }
+                }
+                let (layout, cursor) = (win.layout, win.cursor);
+                match controls::hit(&layout, cursor).filter(|_| pressed && win.controls_shown()) {
+                    Some(control) => win.press_control(control),
+                    None => {
+                        let used = win.scene().mouse_button(&layout, cursor, pressed);
+                        // Without a title bar to grab, the window moves by dragging it wherever the scene doesn't
+                        if pressed
+                            && !used
+                            && self.settings.borderless
+                            && let Err(e) = win.window.drag_window()
+                        {
+                            debug!("Can't move the window by dragging here: {e}");
                         }
-                        Key::Named(NamedKey::Escape) => event_loop.exit(),
-                        _ => {}
                     }
                 }
             }
+
+            WindowEvent::MouseWheel { delta, .. } => {
+                // Scrolling up speeds up, one step per wheel notch. Partial steps add up until they make a whole one.
+                win.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_SCROLL_STEP,
+                };
+                let steps = win.scroll.trunc();
+                if steps != 0.0 {
+                    win.scroll -= steps;
+                    // A fling can report hundreds of steps, more than the whole range is enough
+                    // (change_speed clamps, this just keeps the cast sane)
+                    win.scene().change_speed(steps.clamp(-1000.0, 1000.0) as i32);
+                }
+            }
+
+            WindowEvent::KeyboardInput { event: key_event, .. }
+                if key_event.state == ElementState::Pressed => {
+                debug!(
+                    "Key pressed: logical={:?}, text={:?}",
+                    key_event.logical_key,
+                    key_event.text
+                );
+                self.key(event_loop, id, &key_event.logical_key);
+            }
             _ => {}
-        }   
+        }
     }
 }
 
+/// The window level for the always-on-top setting.
+fn window_level(always_on_top: bool) -> WindowLevel {
+    if always_on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal }
+}
+
+/// Every scene built in, the first one is shown at startup. Each is set up from the settings
+/// with the command-line flags on top: out of range saved values are clamped, out of range
+/// flags are reported and ignored.
+/// `sound` opens the audio output for the metronome click and the watch's chime, drawing a single
+/// frame doesn't need it.
+fn build_scenes(args: &cli::Args, settings: &config::Settings, sound: bool) -> Vec<Box<dyn scene::Scene>> {
+    #[allow(unused_mut)]
+    let mut scenes: Vec<Box<dyn scene::Scene>> = Vec::new();
+    #[cfg(feature = "watch")]
+    {
+        let fps = cli::in_range("--fps", args.fps, MIN_FPS, MAX_FPS)
+            .unwrap_or(settings.fps.clamp(MIN_FPS, MAX_FPS));
+        scenes.push(Box::new(watch::WatchScene::new(fps, args.tz, settings, sound)));
+    }
+    #[cfg(feature = "stopwatch")]
+    scenes.push(Box::new(stopwatch::StopwatchScene::new()));
+    #[cfg(feature = "timer")]
+    {
+        let seconds = cli::in_range("--timer", args.timer, MIN_TIMER, MAX_TIMER)
+            .unwrap_or(settings.timer.clamp(MIN_TIMER, MAX_TIMER));
+        let clicker = if sound { audio::Clicker::new() } else { audio::Clicker::silent() };
+        scenes.push(Box::new(timer::TimerScene::new(seconds, clicker)));
+    }
+    #[cfg(feature = "world")]
+    {
+        let names = args.world.as_ref().unwrap_or(&settings.world_zones);
+        scenes.push(Box::new(world::WorldScene::new(names, settings)));
+    }
+    #[cfg(feature = "metronome")]
+    {
+        let bpm = cli::in_range("--bpm", args.bpm, MIN_BPM, MAX_BPM)
+            .unwrap_or(settings.bpm.clamp(MIN_BPM, MAX_BPM));
+        let clicker = if sound { audio::Clicker::new() } else { audio::Clicker::silent() };
+        scenes.push(Box::new(metronome::MetronomeScene::new(bpm, settings, clicker)));
+    }
+    scenes
+}
+
 fn main() {
-    // 1) Create the event loop on the main thread
-    let event_loop = EventLoop::new().unwrap();
+    // 0) Everything below may log, warnings at least
+    logging::init();
+
+    // 1) Load the saved settings, command-line flags override them for this run.
+    // (Parsed before anything touches the display, so --help works anywhere.)
+    let args = cli::Args::parse();
+    // --config-string replaces the settings file's, and is saved over it like any change
+    let mut settings = args.config_string.clone().unwrap_or_else(config::Settings::load);
+    settings.hand_lengths = settings.hand_lengths.clamped();
+    // Unlike the speeds there's nothing to range-check, the flag just turns it on
+    settings.always_on_top |= args.always_on_top;
+    settings.borderless |= args.borderless;
+    settings.transparent |= args.transparent;
+    settings.power_saving |= args.power_saving;
+    #[cfg(feature = "battery-saver")]
+    {
+        settings.battery_saver |= args.battery_saver;
+    }
 
-    // 2) Run your (empty) app inside that loop
+    // --ntp: ask once how far the system clock is off, the time shown from here on is corrected by it
+    #[cfg(feature = "ntp")]
+    if let Some(server) = &args.ntp {
+        match ntp::query_offset(server) {
+            Ok(correction) => {
+                info!("The system clock is {:.3} s off, going by {server}", correction.as_seconds_f64());
+                time_source::set_correction(correction);
+            }
+            Err(e) => warn!("No time from {server}, showing the system time: {e}"),
+        }
+    }
+
+    // --render: draw a single frame of the current time into a PNG, no window or display needed
+    if let Some(path) = &args.render {
+        // The scene shown at startup
+        let mut scene = build_scenes(&args, &settings, false).swap_remove(0);
+        let palette = settings.palette(scene.kind());
+        // One image pixel per logical pixel, the way a 1x display shows it
+        let layout = layout::Layout::new(WIDTH, HEIGHT, settings.hand_lengths, 1.0, settings.dial_position);
+        let pixels = match args.at {
+            Some(at) => render::render_frame(&layout, scene.as_mut(), &palette, settings.show_hud, &time_source::FakeClock::new(at)),
+            None => render::render_frame(&layout, scene.as_mut(), &palette, settings.show_hud, &time_source::SystemClock),
+        };
+        if let Err(e) = render::save_png(path, &pixels, WIDTH, HEIGHT) {
+            error!("Could not write '{}': {e}", path.display());
+            std::process::exit(1);
+        }
+        println!("Rendered a {WIDTH}x{HEIGHT} frame to '{}'", path.display());
+        return;
+    }
+
+    // 2) Create the event loop on the main thread
+    let event_loop = match EventLoop::new() {
+        Ok(event_loop) => event_loop,
+        Err(e) => fail(AppError::EventLoop(e)),
+    };
+
+    // 3) Run your (empty) app inside that loop, it opens the windows once the loop is running
     let mut app = App {
-        window: None,
-        surface: None,
-        start: Instant::now(),
-        fps: MIN_FPS,
-        next_frame: Instant::now(),
-        #[cfg(feature = "metronome")]
-        bpm: 60, // Default BPM
+        windows: HashMap::new(),
+        icon: icon::resolve_icon(args.icon.as_deref()),
+        // A --icon file comes in one size only, it is used for both
+        #[cfg(windows)]
+        small_icon: args.icon.is_none().then(|| icon::load_icon_embedded_sized(icon::SMALL_ICON)).flatten(),
+        #[cfg(feature = "battery-saver")]
+        power: settings.battery_saver.then(power::PowerProbe::new),
+        settings,
+        frame_timer: (args.stats || cfg!(feature = "frame-timing")).then(timing::FrameTimer::new),
+        #[cfg(feature = "hot-reload")]
+        _settings_watcher: reload::watch(event_loop.create_proxy()),
+        args,
+        error: None,
     };
-    event_loop.run_app(&mut app).unwrap();
+    if let Err(e) = event_loop.run_app(&mut app) {
+        fail(AppError::EventLoop(e));
+    }
+    if let Some(e) = app.error {
+        fail(e);
+    }
+}
+
+/// Report `error` and exit with a failure status.
+fn fail(error: AppError) -> ! {
+    error!("{error}");
+    std::process::exit(1);
 }
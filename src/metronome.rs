@@ -0,0 +1,489 @@
+// The metronome scene: keeps time (counting beats, clicking, tap tempo) and draws
+// the triangular body, the range guide and the swinging arm.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::audio::Clicker;
+use crate::config::{BeatFlash, PendulumPivot, Settings};
+use watchrs::draw::{self, color_rgb, Canvas, Point, Rect};
+use watchrs::font;
+use crate::hud::Hud;
+use crate::layout::Layout;
+use crate::pendulum::Easing;
+use crate::scene::{Scene, SceneKind};
+use crate::theme::ThemePalette;
+use crate::time_source::TimeSource;
+
+pub const MAX_BPM: u32 = 200;
+pub const MIN_BPM: u32 = 20;
+const BPM_STEP: u32 = 5;        // per +/- key press or scroll notch
+const FINE_BPM_STEP: u32 = 1;   // per [/] or up/down arrow key press, for fine-tuning
+/// Degrees the arm swings to either side of vertical, the swing_arc setting is kept within these
+const MIN_SWING_ARC: f32 = 20.0;
+const MAX_SWING_ARC: f32 = 90.0;
+/// Redraws per second the render_fps setting is kept within
+const MIN_RENDER_FPS: u32 = 5;
+const MAX_RENDER_FPS: u32 = 60;
+// Clicks are due this long after their beat, when the beat count (an f32, rounded) has surely gone up.
+// Well below what an ear tells apart, the timing between clicks stays the same.
+const CLICK_MARGIN: Duration = Duration::from_millis(1);
+// Time signatures the T key cycles through
+const BEATS_PER_MEASURE: [u32; 4] = [2, 3, 4, 6];
+// Clicks per beat the S key cycles through: the beat alone, eighths, triplets, sixteenths
+const SUBDIVISIONS: [u32; 4] = [1, 2, 3, 4];
+// Longest count-in, two measures of 4/4
+const MAX_COUNT_IN: u32 = 8;
+// Tap tempo: average over the last few taps, forget them after a pause
+const MAX_TAPS: usize = 4;
+const TAP_RESET: Duration = Duration::from_secs(2);
+// Beat flash: how far towards white the frame goes on a downbeat and on the other beats,
+// and how long it takes to fade, as a fraction of the beat so it's over before the next one
+const DOWNBEAT_FLASH: f32 = 0.9;
+const BEAT_FLASH: f32 = 0.45;
+const FLASH_LENGTH: f32 = 0.3;
+// The background brightens this much of the frame's flash, a full one would be blinding
+const BACKGROUND_FLASH: f32 = 0.25;
+const FLASH_COLOR: u32 = color_rgb(255, 255, 255);
+
+/// The metronome with its swinging arm and click.
+pub struct MetronomeScene {
+    start:      Instant,    // beat zero after the count-in, or the last tempo change
+    start_beats: f32,       // beats counted at `start`: 0, or how far it had come at the tempo change
+    bpm:        u32,
+    beats:      f32,        // beats since beat zero at the last update
+    clicker:    Clicker,
+    muted:      bool,
+    last_beat:  u64,        // whole beats since beat zero at the last update, to notice the next one
+    subdivisions: u32,      // clicks per beat, 1 for the beat alone
+    last_subdivision: u64,  // whole subdivisions since beat zero at the last update, like last_beat
+    beats_per_measure: u32,
+    beat_in_measure:   u32, // 0 is the downbeat
+    taps:       VecDeque<Instant>,
+    arm:        Arm,
+    beat_flash: BeatFlash,
+    count_in:   u32,        // silent beats before the clicks start, 0 for none
+    render_fps: Option<u32>, // redraws per second, None to redraw with every frame of the monitor
+    counting:   Option<u32>, // the count-in number on screen while counting in, None once playing
+}
+
+impl MetronomeScene {
+    pub fn new(bpm: u32, settings: &Settings, clicker: Clicker) -> Self {
+        let mut scene = Self {
+            start: Instant::now(),
+            start_beats: 0.0,
+            bpm,
+            beats: 0.0,
+            clicker,
+            muted: false,
+            last_beat: 0,
+            subdivisions: if SUBDIVISIONS.contains(&settings.subdivisions) { settings.subdivisions } else { 1 },
+            last_subdivision: 0,
+            beats_per_measure: 4,
+            beat_in_measure: 0,
+            taps: VecDeque::with_capacity(MAX_TAPS + 1),
+            arm: Arm {
+                easing: Easing::Harmonic,
+                arc: settings.swing_arc.clamp(MIN_SWING_ARC, MAX_SWING_ARC),
+                pivot: settings.pendulum_pivot,
+            },
+            beat_flash: settings.beat_flash,
+            count_in: settings.count_in.min(MAX_COUNT_IN),
+            render_fps: settings.render_fps.map(|fps| fps.clamp(MIN_RENDER_FPS, MAX_RENDER_FPS)),
+            counting: None,
+        };
+        scene.restart(Instant::now());
+        scene
+    }
+
+    /// Count beats from `now` again, with the arm at the right extreme on a downbeat.
+    /// With a count-in, beat zero is that many beats after `now` and `update` counts down to it.
+    fn restart(&mut self, now: Instant) {
+        let count_in = Duration::from_secs_f32(self.count_in as f32 * 60.0 / self.bpm as f32);
+        self.start = now + count_in;
+        self.start_beats = 0.0;
+        self.counting = (self.count_in > 0).then_some(self.count_in);
+        self.beats = 0.0;
+        self.last_beat = 0;
+        self.last_subdivision = 0;
+        self.beat_in_measure = 0;
+    }
+
+    /// Raise (positive `delta`) or lower the tempo by `delta` BPM, within MIN_BPM..=MAX_BPM.
+    fn add_bpm(&mut self, delta: i64) {
+        let bpm = self.bpm as i64 + delta;
+        self.set_bpm(bpm.clamp(MIN_BPM as i64, MAX_BPM as i64) as u32, Instant::now());
+        debug!("BPM {} to: {}", if delta > 0 { "increased" } else { "reduced" }, self.bpm);
+    }
+
+    /// Tap tempo: set the BPM from the average interval between the last few taps.
+    fn tap_tempo(&mut self) {
+        let now = Instant::now();
+
+        // A long pause means a new tempo is being tapped, old taps would only skew it
+        if self.taps.back().is_some_and(|&last| now - last > TAP_RESET) {
+            self.taps.clear();
+        }
+        self.taps.push_back(now);
+        if self.taps.len() > MAX_TAPS {
+            self.taps.pop_front();
+        }
+
+        // Need at least two taps for one interval
+        if self.taps.len() < 2 {
+            return;
+        }
+        let interval = (now - self.taps[0]).as_secs_f32() / (self.taps.len() - 1) as f32;
+        self.set_bpm(((60.0 / interval).round() as u32).clamp(MIN_BPM, MAX_BPM), now);
+        debug!("Tapped BPM: {}", self.bpm);
+    }
+
+    /// Change the tempo to `bpm` at `now`, going on from where the beat count is: the arm carries on
+    /// from where it is at the new speed, and the clicks with it. Counted from the old start at the
+    /// new tempo, the count would jump (a minute at 120 BPM is beat 121 at 121 BPM), the arm with it,
+    /// clicking beats that weren't played and throwing the measure off.
+    ///
+    /// So the count starts over at `now` from the beats reached, never fewer than the last update
+    /// counted: last_beat and last_subdivision still hold, and a beat that came due since that
+    /// update still clicks on the next one.
+    fn set_bpm(&mut self, bpm: u32, now: Instant) {
+        if bpm == self.bpm {
+            return;
+        }
+        match now.checked_duration_since(self.start) {
+            Some(elapsed) => {
+                let beats = self.start_beats + elapsed.as_secs_f32() * self.bpm as f32 / 60.0;
+                self.start = now;
+                self.start_beats = beats.max(self.beats);
+            }
+            // Counting in: as many beats left, at the new tempo
+            None => {
+                let beats_left = (self.start - now).as_secs_f32() * self.bpm as f32 / 60.0;
+                self.start = now + Duration::from_secs_f32(beats_left * 60.0 / bpm as f32);
+            }
+        }
+        self.bpm = bpm;
+    }
+}
+
+impl Scene for MetronomeScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Metronome
+    }
+
+    fn activate(&mut self, now: Instant) {
+        // Start over from the right extreme on a downbeat, instead of clicking at once for the beats missed
+        self.restart(now);
+    }
+
+    fn reset(&mut self, now: Instant) {
+        self.restart(now);
+        info!("Metronome restarted");
+    }
+
+    // Capped, the arm moves in steps but the clicks still come on the beat: see next_click
+    fn frame_interval(&self) -> Option<Duration> {
+        self.render_fps.map(|fps| Duration::from_secs_f32(1.0 / fps as f32))
+    }
+
+    // Every subdivision clicks (the beat's own or a softer one between beats), the next one is due next.
+    // Counting in, that's beat zero. Without a count-in beat zero doesn't click, the first one is
+    // subdivision 1, which last_subdivision (0 at the start) gives too.
+    fn next_click(&self) -> Option<Instant> {
+        if self.muted {
+            return None;
+        }
+        let subdivision = if self.counting.is_some() { 0 } else { self.last_subdivision + 1 };
+        // Right away for one that came due before a tempo change
+        let beats = (subdivision as f64 / self.subdivisions as f64 - self.start_beats as f64).max(0.0);
+        Some(self.start + Duration::from_secs_f64(beats * 60.0 / self.bpm as f64) + CLICK_MARGIN)
+    }
+
+    fn update(&mut self, time: &dyn TimeSource) {
+        let now = time.instant();
+        let beat_interval = 60.0 / (self.bpm as f32);
+
+        // Counting in: silent, the arm waits at the right extreme and the number shows the beats left.
+        // Beat zero ends it with the downbeat's click, which playing alone would skip (it clicks as a
+        // beat count goes up, the first time on beat one).
+        let Some(elapsed) = now.checked_duration_since(self.start) else {
+            let beats_left = (self.start - now).as_secs_f32() / beat_interval;
+            self.counting = Some((beats_left.ceil() as u32).max(1));
+            return;
+        };
+        if self.counting.take().is_some() && !self.muted {
+            self.clicker.click(true);
+        }
+
+        // Beats since beat zero, both the click and the arm are driven by it (see pendulum.rs)
+        self.beats = self.start_beats + elapsed.as_secs_f32() / beat_interval;
+
+        // Click whenever the whole number of beats since start goes up,
+        // that is exactly when the arm reaches an extreme.
+        let beat = self.beats as u64;
+        if beat != self.last_beat {
+            self.last_beat = beat;
+            self.beat_in_measure = (self.beat_in_measure + 1) % self.beats_per_measure;
+            if !self.muted {
+                self.clicker.click(self.beat_in_measure == 0);
+            }
+        }
+
+        // The subdivisions come from the same beat count, at every k/subdivisions of a beat.
+        // The one on the beat is the beat's own click above, only those between beats click softly.
+        let subdivision = (self.beats * self.subdivisions as f32) as u64;
+        if subdivision != self.last_subdivision {
+            self.last_subdivision = subdivision;
+            if !subdivision.is_multiple_of(self.subdivisions as u64) && !self.muted {
+                self.clicker.click_subdivision();
+            }
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, _buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>> {
+        // Nothing flashes or lights up before the first beat
+        let downbeat = self.beat_in_measure == 0 && self.counting.is_none();
+        let flash = if self.counting.is_some() { 0.0 } else { flash_strength(self.beats, downbeat) };
+        let (palette, frame) = flashed(palette, self.beat_flash, flash);
+        draw(canvas, layout, &palette, frame, self.arm, self.beats, downbeat, self.subdivisions);
+        if let Some(count) = self.counting {
+            draw_count(canvas, layout, &palette, count);
+        }
+        hud.draw(canvas, layout, &palette);
+        None
+    }
+
+    fn hud_text(&self) -> String {
+        format!("{} BPM", self.bpm)
+    }
+
+    fn key(&mut self, key: &Key) {
+        match key {
+            Key::Named(NamedKey::Space) => self.tap_tempo(),
+            Key::Character(s) if s == "t" => {
+                // Next time signature, restarting the measure so the next beat is a downbeat
+                let i = BEATS_PER_MEASURE.iter().position(|&n| n == self.beats_per_measure).unwrap_or(0);
+                self.beats_per_measure = BEATS_PER_MEASURE[(i + 1) % BEATS_PER_MEASURE.len()];
+                self.beat_in_measure = self.beats_per_measure - 1;
+                info!("Time signature: {}/4", self.beats_per_measure);
+            }
+            Key::Character(s) if s == "m" => {
+                self.muted = !self.muted;
+                info!("Click {}", if self.muted { "muted" } else { "unmuted" });
+            }
+            Key::Character(s) if s == "s" => {
+                let i = SUBDIVISIONS.iter().position(|&n| n == self.subdivisions).unwrap_or(0);
+                self.subdivisions = SUBDIVISIONS[(i + 1) % SUBDIVISIONS.len()];
+                // Counted afresh from here, the change alone shouldn't click
+                self.last_subdivision = (self.beats * self.subdivisions as f32) as u64;
+                info!("Subdivisions: {} per beat", self.subdivisions);
+            }
+            Key::Character(s) if s == "v" => {
+                self.beat_flash = self.beat_flash.next();
+                info!("Beat flash: {:?}", self.beat_flash);
+            }
+            Key::Character(s) if s == "e" => {
+                self.arm.easing = self.arm.easing.toggled();
+                info!("Pendulum easing: {:?}", self.arm.easing);
+            }
+            Key::Character(s) if s == "]" => self.add_bpm(FINE_BPM_STEP as i64),
+            Key::Character(s) if s == "[" => self.add_bpm(-(FINE_BPM_STEP as i64)),
+            Key::Named(NamedKey::ArrowUp) => self.add_bpm(FINE_BPM_STEP as i64),
+            Key::Named(NamedKey::ArrowDown) => self.add_bpm(-(FINE_BPM_STEP as i64)),
+            _ => {}
+        }
+    }
+
+    /// Raise (positive `steps`) or lower the tempo by BPM_STEP per step, within MIN_BPM..=MAX_BPM.
+    fn change_speed(&mut self, steps: i32) {
+        self.add_bpm(steps as i64 * BPM_STEP as i64);
+    }
+
+    #[cfg(feature = "gui")]
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.set_bpm(settings.bpm.clamp(MIN_BPM, MAX_BPM), Instant::now());
+        self.beat_flash = settings.beat_flash;
+        if SUBDIVISIONS.contains(&settings.subdivisions) && settings.subdivisions != self.subdivisions {
+            self.subdivisions = settings.subdivisions;
+            // Counted afresh from here like with the S key
+            self.last_subdivision = (self.beats * self.subdivisions as f32) as u64;
+        }
+    }
+
+    fn save(&self, settings: &mut Settings) {
+        settings.bpm = self.bpm;
+        settings.beat_flash = self.beat_flash;
+        settings.subdivisions = self.subdivisions;
+    }
+}
+
+/// How the arm swings: its easing (E key), how far and from where.
+#[derive(Copy, Clone, Debug)]
+pub struct Arm {
+    pub easing: Easing,
+    pub arc:    f32,            // degrees to either side of vertical
+    pub pivot:  PendulumPivot,
+}
+
+impl Arm {
+    /// Screen angle of the arm `beats` beats after the start.
+    fn angle(self, beats: f32) -> f32 {
+        self.easing.arm_angle(beats, self.arc, self.pivot == PendulumPivot::Top)
+    }
+
+    /// Where the arm swings from and how long it is.
+    fn pivot_and_length(self, layout: &Layout) -> (Point, usize) {
+        match self.pivot {
+            PendulumPivot::Bottom => (layout.center, layout.metronome.hand_length),
+            PendulumPivot::Top => (layout.metronome.triangle[0], layout.metronome.hanging_length),
+        }
+    }
+}
+
+/// How bright the beat flash is `beats` beats after the start, 0.0 (none) to 1.0 (white).
+/// It starts at its brightest on the beat, the downbeat's brighter, and fades out over FLASH_LENGTH of the beat.
+fn flash_strength(beats: f32, downbeat: bool) -> f32 {
+    let peak = if downbeat { DOWNBEAT_FLASH } else { BEAT_FLASH };
+    peak * (1.0 - beats.fract() / FLASH_LENGTH).max(0.0)
+}
+
+/// The palette and frame color to draw with while the flash is `strength` bright: the frame
+/// brightened, and for `BeatFlash::Background` the background too.
+fn flashed(palette: &ThemePalette, beat_flash: BeatFlash, strength: f32) -> (ThemePalette, u32) {
+    let mut palette = *palette;
+    if beat_flash == BeatFlash::Off || strength <= 0.0 {
+        return (palette, palette.foreground);
+    }
+    let frame = draw::lerp_color(palette.foreground, FLASH_COLOR, strength);
+    if beat_flash == BeatFlash::Background {
+        let strength = strength * BACKGROUND_FLASH;
+        palette.background = draw::lerp_color(palette.background, FLASH_COLOR, strength);
+        palette.background_bottom = palette.background_bottom.map(|bottom| draw::lerp_color(bottom, FLASH_COLOR, strength));
+    }
+    (palette, frame)
+}
+
+/// Draw the whole metronome with the arm where it is `beats` beats after the start.
+/// `downbeat` lights up the pivot early in the beat, for the first beat of a measure.
+/// The frame is drawn in `frame`, the foreground unless the beat flashes it.
+/// With more than one of `subdivisions` per beat, small ticks on the guide mark where the arm is at each.
+#[allow(clippy::too_many_arguments)]
+pub fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, frame: u32, arm: Arm, beats: f32, downbeat: bool, subdivisions: u32) {
+    let metronome = layout.metronome;
+    let thick = layout.thick;
+    let (pivot, length) = arm.pivot_and_length(layout);
+    palette.fill_background(canvas);
+    canvas.draw_frame(layout.frame_padding, thick, frame);
+
+    // The housing: a solid body in a faint shade, then its outline
+    let [top_point, left_point, right_point] = metronome.triangle;
+    canvas.fill_polygon(&metronome.triangle, palette.housing);
+    canvas.draw_line(top_point, left_point, thick, palette.foreground);
+    canvas.draw_line(top_point, right_point, thick, palette.foreground);
+    canvas.draw_line(right_point, left_point, thick, palette.foreground);
+
+    // Faint guide along the range the arm swings through, clockwise from one extreme to the other:
+    // over the top from the left one for an upright arm, under the pivot from the right one for a hanging arm
+    let guide_color = draw::blend(palette.background, palette.foreground, 0.3);
+    let (left, right) = (arm.angle(1.0), arm.angle(0.0));
+    let (guide_start, guide_end) = match arm.pivot {
+        PendulumPivot::Bottom => (left, right),
+        PendulumPivot::Top => (right, left),
+    };
+    canvas.draw_arc(pivot, length, guide_start, guide_end, (thick / 3).max(1), guide_color);
+
+    // Subdivision ticks across the guide. The arm swings one way on a beat and back on the next,
+    // so each subdivision sits at two mirrored places (or one, halfway, in the middle).
+    let tick_length = thick;
+    for j in 1..subdivisions {
+        let fraction = j as f32 / subdivisions as f32;
+        for beat in [0.0, 1.0] {
+            let angle = arm.angle(beat + fraction);
+            let (inner, outer) = (length.saturating_sub(tick_length), length + tick_length);
+            canvas.draw_tick(pivot, inner, outer, angle, (thick / 3).max(1), guide_color);
+        }
+    }
+
+    let hand_tip = pivot.polar(arm.angle(beats), length as f32);
+    #[cfg(feature = "aa")]
+    canvas.draw_line_aa(pivot, hand_tip, thick, palette.foreground);
+    #[cfg(not(feature = "aa"))]
+    canvas.draw_line(pivot, hand_tip, thick, palette.foreground);
+
+    // Pivot the arm swings around
+    canvas.draw_filled_circle(pivot, thick, palette.foreground);
+
+    // Accent the downbeat: light up the pivot for the first quarter of the beat
+    if downbeat && beats.fract() < 0.25 {
+        canvas.draw_filled_circle(pivot, thick * 2, palette.accent);
+    }
+}
+
+/// The count-in number, large, in the housing under the pivot.
+fn draw_count(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, count: u32) {
+    let metronome = layout.metronome;
+    let scale = metronome.count_in_scale;
+    let text = count.to_string();
+    let base_y = metronome.triangle[1].y;
+    let center = Point::new(layout.center.x, layout.center.y + (base_y - layout.center.y) / 2);
+    let origin = Point::new(
+        center.x - (font::text_width(&text, scale) / 2) as isize,
+        center.y - (font::text_height(scale) / 2) as isize,
+    );
+    canvas.draw_text(origin, &text, scale, palette.foreground);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::time_source::FakeClock;
+
+    /// Playing at `bpm` from the clock's now, without a count-in.
+    fn playing(bpm: u32, clock: &FakeClock) -> MetronomeScene {
+        let mut scene = MetronomeScene::new(bpm, &Settings::default(), Clicker::silent());
+        scene.count_in = 0;
+        scene.restart(clock.instant());
+        scene
+    }
+
+    #[test]
+    fn tempo_change_keeps_the_beat_count() {
+        let mut clock = FakeClock::new(Utc::now());
+        let mut scene = playing(120, &clock);
+        // A minute and a quarter beat in at 120 BPM
+        clock.advance(Duration::from_millis(60_125));
+        scene.update(&clock);
+        let (beats, last_beat, beat_in_measure) = (scene.beats, scene.last_beat, scene.beat_in_measure);
+        assert_eq!(last_beat, 120);
+
+        scene.set_bpm(121, clock.instant());
+        scene.update(&clock);
+        assert!((scene.beats - beats).abs() < 1e-3, "{} beats, was {beats}", scene.beats);
+        assert_eq!((scene.last_beat, scene.beat_in_measure), (last_beat, beat_in_measure));
+
+        // And on at the new tempo: the rest of the beat, then one more
+        clock.advance(Duration::from_secs_f64(60.0 / 121.0));
+        scene.update(&clock);
+        assert_eq!(scene.last_beat, 121);
+        assert_eq!(scene.beat_in_measure, (beat_in_measure + 1) % scene.beats_per_measure);
+    }
+
+    #[test]
+    fn tempo_change_while_counting_in_keeps_the_beats_left() {
+        let mut clock = FakeClock::new(Utc::now());
+        let mut scene = playing(60, &clock);
+        scene.count_in = 4;
+        scene.restart(clock.instant());
+        // Two and a half of four beats left at 60 BPM, as many at 120
+        clock.advance(Duration::from_millis(1_500));
+        scene.set_bpm(120, clock.instant());
+        assert_eq!(scene.start, clock.instant() + Duration::from_millis(1_250));
+        scene.update(&clock);
+        assert_eq!(scene.counting, Some(3));
+    }
+}
@@ -0,0 +1,135 @@
+// Setting the clock by an NTP server (--ntp): one SNTP query at startup (RFC 4330), std only.
+//
+// The answer says how far the system clock is off, and from then on that correction is added to
+// every wall-clock reading (see time_source.rs). The system clock itself isn't touched, and a clock
+// that keeps drifting while the app runs isn't followed: it's asked once.
+//
+// The query: a 48-byte request over UDP, the server's receive (t2) and transmit (t3) timestamps in the
+// reply, and the offset ((t2 - t1) + (t3 - t4)) / 2 with our send (t1) and receive (t4) times. That
+// cancels the network delay out as long as it's the same both ways. No answer within NTP_TIMEOUT,
+// or one that isn't a usable time, and main.rs goes on with the system time.
+
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::TimeDelta;
+
+const NTP_PORT: u16 = 123;
+/// How long to wait for the answer, the window opens after it
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+const PACKET_SIZE: usize = 48;
+// First byte of the request: no leap second warning, version 4, mode 3 (client)
+const CLIENT_REQUEST: u8 = 0b00_100_011;
+// Mode of a server's reply, in the low 3 bits of the first byte
+const MODE_SERVER: u8 = 4;
+// Seconds from the NTP epoch (1900) to the Unix one (1970)
+const NTP_TO_UNIX: u64 = 2_208_988_800;
+
+/// Why the query gave no time, with the step it went wrong at.
+pub enum NtpError {
+    Resolve(io::Error),         // looking up the server's address
+    NoAddress,                  // the name has no address
+    Socket(io::Error),          // sending, or receiving within NTP_TIMEOUT
+    BadReply(&'static str),     // an answer that isn't a usable time
+}
+
+impl fmt::Display for NtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NtpError::Resolve(e) => write!(f, "could not look up the server: {e}"),
+            NtpError::NoAddress => write!(f, "the server name has no address"),
+            NtpError::Socket(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                write!(f, "no answer within {} s", NTP_TIMEOUT.as_secs())
+            }
+            NtpError::Socket(e) => write!(f, "{e}"),
+            NtpError::BadReply(why) => write!(f, "unusable answer, {why}"),
+        }
+    }
+}
+
+/// Ask `server` ("host" or "host:port") how far the system clock is off: the returned correction
+/// added to the system time gives the server's.
+pub fn query_offset(server: &str) -> Result<TimeDelta, NtpError> {
+    // With a port of its own first, then on the NTP port
+    let address = server
+        .to_socket_addrs()
+        .or_else(|_| (server, NTP_PORT).to_socket_addrs())
+        .map_err(NtpError::Resolve)?
+        .next()
+        .ok_or(NtpError::NoAddress)?;
+    let socket = open_socket(address).map_err(NtpError::Socket)?;
+
+    let mut request = [0; PACKET_SIZE];
+    request[0] = CLIENT_REQUEST;
+    // Our send time goes out as the transmit timestamp and comes back as the originate one,
+    // a reply that doesn't carry it answers some other request
+    let sent = SystemTime::now();
+    request[40..48].copy_from_slice(&to_timestamp(sent));
+    socket.send(&request).map_err(NtpError::Socket)?;
+
+    let mut reply = [0; PACKET_SIZE];
+    let length = socket.recv(&mut reply).map_err(NtpError::Socket)?;
+    let received = SystemTime::now();
+
+    if length < PACKET_SIZE {
+        return Err(NtpError::BadReply("too short"));
+    }
+    if reply[0] & 0b111 != MODE_SERVER {
+        return Err(NtpError::BadReply("not from a server"));
+    }
+    // Stratum 0 is a "kiss-o'-death": the server won't say, come back later or ask another
+    if reply[1] == 0 {
+        return Err(NtpError::BadReply("the server turned the request down"));
+    }
+    if reply[24..32] != request[40..48] {
+        return Err(NtpError::BadReply("it answers another request"));
+    }
+    let (server_received, server_sent) = (from_timestamp(&reply[32..40]), from_timestamp(&reply[40..48]));
+    if server_sent == 0.0 {
+        return Err(NtpError::BadReply("no time in it"));
+    }
+
+    let (sent, received) = (unix_seconds(sent), unix_seconds(received));
+    let offset = ((server_received - sent) + (server_sent - received)) / 2.0;
+    Ok(TimeDelta::microseconds((offset * 1e6).round() as i64))
+}
+
+/// A UDP socket talking to `address` only, giving up on a receive after NTP_TIMEOUT.
+fn open_socket(address: SocketAddr) -> io::Result<UdpSocket> {
+    let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(local)?;
+    socket.set_read_timeout(Some(NTP_TIMEOUT))?;
+    socket.connect(address)?;
+    Ok(socket)
+}
+
+/// Seconds since the Unix epoch, fraction included.
+fn unix_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
+
+/// `time` as an NTP timestamp: whole seconds since 1900 (wrapping every 136 years) and the
+/// fraction in 1/2³² s, both big-endian.
+fn to_timestamp(time: SystemTime) -> [u8; 8] {
+    let since_unix = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = (since_unix.as_secs() + NTP_TO_UNIX) as u32;
+    let fraction = ((since_unix.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    let mut timestamp = [0; 8];
+    timestamp[..4].copy_from_slice(&seconds.to_be_bytes());
+    timestamp[4..].copy_from_slice(&(fraction as u32).to_be_bytes());
+    timestamp
+}
+
+/// Seconds since the Unix epoch for an NTP timestamp, 0.0 for an unset (all zero) one.
+fn from_timestamp(bytes: &[u8]) -> f64 {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f64 / (1u64 << 32) as f64;
+    if seconds == 0 && fraction == 0.0 {
+        return 0.0;
+    }
+    // The seconds wrap in 2036, ones with the top bit clear are from after that (RFC 4330)
+    let seconds = if seconds & 0x8000_0000 == 0 { seconds + (1 << 32) } else { seconds };
+    (seconds - NTP_TO_UNIX) as f64 + fraction
+}
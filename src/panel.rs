@@ -0,0 +1,387 @@
+// The settings panel: sliders, choices, color swatches and toggles over the scene for the settings
+// a mouse can change without the settings file, opened and closed with the G key (Escape closes it
+// too). Drawn as part of the hud like the key help, in the middle of the window.
+//
+// A minimal immediate-mode UI: no widget is kept anywhere. `rows` builds them afresh from the
+// settings for every frame and every click, so the panel always shows what the settings say. A
+// click (or a slider drag) writes into the settings, main.rs then applies them to the window and the
+// scenes and redraws. The speeds and scene options live in the scenes while they run, main.rs copies
+// them into the settings (`Scene::save`) before the panel reads them.
+
+use std::ops::RangeInclusive;
+
+use watchrs::draw::{self, Canvas, Dimensions, Point, Rect};
+use watchrs::font;
+use crate::config::{Rgb, Settings};
+#[cfg(feature = "metronome")]
+use crate::config::BeatFlash;
+#[cfg(feature = "watch")]
+use crate::config::Numerals;
+use crate::layout::Layout;
+use crate::scene::SceneKind;
+use crate::theme::{Theme, ThemePalette};
+#[cfg(feature = "watch")]
+use crate::{clock, watch};
+#[cfg(feature = "metronome")]
+use crate::metronome;
+
+// Like the key help, the scene shows through a little
+const PANEL_OPACITY: f32 = 0.9;
+// Unselected buttons and the slider track, as a share of the foreground over the background
+const BUTTON_SHADE: f32 = 0.2;
+const TRACK_SHADE: f32 = 0.5;
+
+/// Colors offered for the background and the foreground, after the theme's own.
+const SWATCHES: [Rgb; 8] = [
+    Rgb::new(255, 255, 255),
+    Rgb::new(0, 0, 0),
+    Rgb::new(75, 95, 100),
+    Rgb::new(0, 200, 255),
+    Rgb::new(0, 255, 30),
+    Rgb::new(255, 176, 0),
+    Rgb::new(255, 80, 70),
+    Rgb::new(180, 140, 255),
+];
+
+const THEMES: [(&str, Theme); 3] = [("dark", Theme::Dark), ("light", Theme::Light), ("contrast", Theme::HighContrast)];
+#[cfg(feature = "watch")]
+const NUMERALS: [(&str, Numerals); 3] = [("1 2 3", Numerals::Arabic), ("I II III", Numerals::Roman), ("none", Numerals::None)];
+#[cfg(feature = "metronome")]
+const BEAT_FLASHES: [(&str, BeatFlash); 3] = [("off", BeatFlash::Off), ("frame", BeatFlash::Frame), ("screen", BeatFlash::Background)];
+#[cfg(feature = "metronome")]
+const SUBDIVISIONS: [(&str, u32); 4] = [("1", 1), ("2", 2), ("3", 3), ("4", 4)];
+#[cfg(feature = "watch")]
+const HOURS: [(&str, bool); 2] = [("12", true), ("24", false)];
+
+/// A setting the panel changes, one per row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    #[cfg(feature = "watch")]
+    Fps,
+    #[cfg(feature = "watch")]
+    Hours,
+    #[cfg(feature = "watch")]
+    Numerals,
+    #[cfg(feature = "watch")]
+    Date,
+    #[cfg(feature = "watch")]
+    MoonPhase,
+    #[cfg(feature = "metronome")]
+    Bpm,
+    #[cfg(feature = "metronome")]
+    Subdivisions,
+    #[cfg(feature = "metronome")]
+    BeatFlash,
+    Theme,
+    Background,
+    Foreground,
+    ShowHud,
+    AlwaysOnTop,
+    Borderless,
+}
+
+/// How a row shows its setting, with the current value.
+#[derive(Clone, Debug)]
+pub enum Widget {
+    Slider { value: u32, range: RangeInclusive<u32> },
+    Toggle(bool),
+    /// Buttons side by side, the selected one filled.
+    Choice { options: Vec<&'static str>, selected: Option<usize> },
+    /// The theme's color first, then SWATCHES. `selected` is None for a color set in the settings file.
+    Swatches { theme: u32, selected: Option<usize> },
+}
+
+/// One setting: its name on the left, its widget on the right.
+#[derive(Clone, Debug)]
+pub struct Row {
+    pub field:  Field,
+    pub label:  &'static str,
+    pub widget: Widget,
+}
+
+/// The rows for `kind`, its own settings first, with the values in `settings`.
+pub fn rows(settings: &Settings, kind: SceneKind) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let row = |field, label, widget| Row { field, label, widget };
+    match kind {
+        #[cfg(feature = "watch")]
+        SceneKind::Watch => {
+            let twelve_hour = settings.twelve_hour.unwrap_or_else(clock::locale_prefers_12_hour);
+            rows.extend([
+                row(Field::Fps, "FPS", Widget::Slider { value: settings.fps, range: watch::MIN_FPS..=watch::MAX_FPS }),
+                row(Field::Hours, "Hours", choice(&HOURS, twelve_hour)),
+                row(Field::Numerals, "Numerals", choice(&NUMERALS, settings.numerals)),
+                row(Field::Date, "Date", Widget::Toggle(settings.complications.date)),
+                row(Field::MoonPhase, "Moon phase", Widget::Toggle(settings.complications.moon_phase)),
+            ]);
+        }
+        #[cfg(feature = "metronome")]
+        SceneKind::Metronome => rows.extend([
+            row(Field::Bpm, "BPM", Widget::Slider { value: settings.bpm, range: metronome::MIN_BPM..=metronome::MAX_BPM }),
+            row(Field::Subdivisions, "Clicks per beat", choice(&SUBDIVISIONS, settings.subdivisions)),
+            row(Field::BeatFlash, "Beat flash", choice(&BEAT_FLASHES, settings.beat_flash)),
+        ]),
+        _ => {}
+    }
+    let theme = settings.theme.colors(kind);
+    rows.extend([
+        row(Field::Theme, "Theme", choice(&THEMES, settings.theme)),
+        row(Field::Background, "Background", swatches(theme.background, settings.background)),
+        row(Field::Foreground, "Foreground", swatches(theme.foreground, settings.foreground)),
+        row(Field::ShowHud, "Speed readout", Widget::Toggle(settings.show_hud)),
+        row(Field::AlwaysOnTop, "Always on top", Widget::Toggle(settings.always_on_top)),
+        row(Field::Borderless, "Borderless", Widget::Toggle(settings.borderless)),
+    ]);
+    rows
+}
+
+/// A choice between `options`, with the one for `value` selected.
+fn choice<T: PartialEq>(options: &[(&'static str, T)], value: T) -> Widget {
+    Widget::Choice {
+        options: options.iter().map(|&(label, _)| label).collect(),
+        selected: options.iter().position(|(_, option)| *option == value),
+    }
+}
+
+/// Color swatches with `color` selected, the theme's (drawn in `theme`) when it's unset.
+fn swatches(theme: u32, color: Option<Rgb>) -> Widget {
+    let selected = match color {
+        None => Some(0),
+        Some(color) => SWATCHES.iter().position(|&swatch| swatch == color).map(|i| i + 1),
+    };
+    Widget::Swatches { theme, selected }
+}
+
+impl Field {
+    /// Whether dragging changes it, rather than only clicking.
+    pub fn slides(self) -> bool {
+        match self {
+            #[cfg(feature = "watch")]
+            Field::Fps => true,
+            #[cfg(feature = "metronome")]
+            Field::Bpm => true,
+            _ => false,
+        }
+    }
+
+    /// Write the value `along` its widget into `settings`, from 0.0 at the widget's left end to 1.0 at
+    /// its right: a slider's value, the button or swatch there. A toggle flips wherever it's clicked.
+    fn set(self, settings: &mut Settings, along: f32) {
+        let along = along.clamp(0.0, 1.0);
+        let pick = |count: usize| ((along * count as f32) as usize).min(count - 1);
+        let slide = |range: RangeInclusive<u32>| range.start() + (along * (range.end() - range.start()) as f32).round() as u32;
+        let swatch = |i: usize| i.checked_sub(1).map(|i| SWATCHES[i]);
+        match self {
+            #[cfg(feature = "watch")]
+            Field::Fps => settings.fps = slide(watch::MIN_FPS..=watch::MAX_FPS),
+            #[cfg(feature = "watch")]
+            Field::Hours => settings.twelve_hour = Some(HOURS[pick(HOURS.len())].1),
+            #[cfg(feature = "watch")]
+            Field::Numerals => settings.numerals = NUMERALS[pick(NUMERALS.len())].1,
+            #[cfg(feature = "watch")]
+            Field::Date => settings.complications.date = !settings.complications.date,
+            #[cfg(feature = "watch")]
+            Field::MoonPhase => settings.complications.moon_phase = !settings.complications.moon_phase,
+            #[cfg(feature = "metronome")]
+            Field::Bpm => settings.bpm = slide(metronome::MIN_BPM..=metronome::MAX_BPM),
+            #[cfg(feature = "metronome")]
+            Field::Subdivisions => settings.subdivisions = SUBDIVISIONS[pick(SUBDIVISIONS.len())].1,
+            #[cfg(feature = "metronome")]
+            Field::BeatFlash => settings.beat_flash = BEAT_FLASHES[pick(BEAT_FLASHES.len())].1,
+            Field::Theme => settings.theme = THEMES[pick(THEMES.len())].1,
+            Field::Background => settings.background = swatch(pick(SWATCHES.len() + 1)),
+            Field::Foreground => settings.foreground = swatch(pick(SWATCHES.len() + 1)),
+            Field::ShowHud => settings.show_hud = !settings.show_hud,
+            Field::AlwaysOnTop => settings.always_on_top = !settings.always_on_top,
+            Field::Borderless => settings.borderless = !settings.borderless,
+        }
+    }
+}
+
+/// Where everything goes at one text scale.
+struct Placement {
+    scale:      usize,
+    panel:      (Point, Dimensions),
+    label_x:    isize,
+    widgets:    Vec<Rect>,  // one per row, the part that takes clicks
+    value_width: usize,     // room a slider leaves for its value on the right
+}
+
+/// Lay `rows` out in the middle of the window, the text starting at the speed readout's size and
+/// getting smaller until the panel fits the frame (like the key help).
+fn place(layout: &Layout, rows: &[Row]) -> Placement {
+    let measure = |scale: usize| {
+        let (text, padding, gap) = (font::text_height(scale), font::text_height(scale), font::text_width("   ", scale));
+        let side = text * 2;            // a toggle box, a swatch, the height of every widget
+        let line = side + text / 2;
+        let value_width = font::text_width("000", scale);
+        let labels = rows.iter().map(|row| font::text_width(row.label, scale)).max().unwrap_or(0);
+        let widgets = rows
+            .iter()
+            .map(|row| match &row.widget {
+                Widget::Slider { .. } => side * 8 + gap + value_width,
+                Widget::Toggle(_) => side,
+                Widget::Choice { options, .. } => {
+                    options.len() * (options.iter().map(|option| font::text_width(option, scale)).max().unwrap_or(0) + side)
+                }
+                Widget::Swatches { .. } => (SWATCHES.len() + 1) * side * 5 / 4,
+            })
+            .max()
+            .unwrap_or(0);
+        let size = Dimensions {
+            width: padding * 2 + labels + gap + widgets,
+            height: (padding * 2 + rows.len() * line).saturating_sub(line - side),
+        };
+        (size, padding, labels + gap, widgets, side, line, value_width)
+    };
+    let inset = layout.frame_padding + layout.thick * 2;
+    let room = Dimensions { width: layout.width.saturating_sub(2 * inset), height: layout.height.saturating_sub(2 * inset) };
+    let scale = (1..=layout.hud_scale)
+        .rev()
+        .find(|&scale| {
+            let (size, ..) = measure(scale);
+            size.width <= room.width && size.height <= room.height
+        })
+        .unwrap_or(1);
+    let (size, padding, widget_x, widget_width, side, line, value_width) = measure(scale);
+
+    let top_left = Point::new(
+        layout.width as isize / 2 - size.width as isize / 2,
+        layout.height as isize / 2 - size.height as isize / 2,
+    );
+    let x0 = (top_left.x.max(0) as usize) + padding + widget_x;
+    let widgets = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let y0 = top_left.y.max(0) as usize + padding + i * line;
+            let width = if matches!(row.widget, Widget::Toggle(_)) { side } else { widget_width };
+            Rect { x0, y0, x1: x0 + width, y1: y0 + side }
+        })
+        .collect();
+    Placement { scale, panel: (top_left, size), label_x: top_left.x + padding as isize, widgets, value_width }
+}
+
+/// The part of a row's widget that takes presses and `along` is measured across: a slider's track
+/// (not its value), all of anything else.
+fn track(placement: &Placement, row: &Row, widget: Rect) -> Rect {
+    match row.widget {
+        Widget::Slider { .. } => {
+            let value = placement.value_width + font::text_width("   ", placement.scale);
+            Rect { x1: widget.x1.saturating_sub(value).max(widget.x0 + 1), ..widget }
+        }
+        _ => widget,
+    }
+}
+
+/// How far along `rect` the x of `point` is, 0.0 at its left edge to 1.0 at its right (and beyond).
+fn along(rect: Rect, point: Point) -> f32 {
+    (point.x as f32 - rect.x0 as f32) / rect.width().max(1) as f32
+}
+
+fn contains(rect: Rect, point: Point) -> bool {
+    let (x, y) = (point.x as usize, point.y as usize);
+    point.x >= 0 && point.y >= 0 && (rect.x0..rect.x1).contains(&x) && (rect.y0..rect.y1).contains(&y)
+}
+
+/// Whether the panel for `kind` covers `point`, so a press there is the panel's even between widgets.
+pub fn covers(layout: &Layout, settings: &Settings, kind: SceneKind, point: Point) -> bool {
+    let (top_left, size) = place(layout, &rows(settings, kind)).panel;
+    let panel = Rect::around(&[top_left, Point::new(top_left.x + size.width as isize, top_left.y + size.height as isize)], 0, layout.width, layout.height);
+    contains(panel, point)
+}
+
+/// Change the setting under `point` in `settings` and return it, `None` if no widget is there.
+pub fn press(layout: &Layout, settings: &mut Settings, kind: SceneKind, point: Point) -> Option<Field> {
+    let rows = rows(settings, kind);
+    let placement = place(layout, &rows);
+    let (row, track) = rows
+        .iter()
+        .zip(&placement.widgets)
+        .map(|(row, &widget)| (row, track(&placement, row, widget)))
+        .find(|&(_, track)| contains(track, point))?;
+    row.field.set(settings, along(track, point));
+    Some(row.field)
+}
+
+/// Move the slider for `field` to `point`, dragged there after a `press` on it. Only x counts.
+pub fn drag(layout: &Layout, settings: &mut Settings, kind: SceneKind, field: Field, point: Point) {
+    let rows = rows(settings, kind);
+    let placement = place(layout, &rows);
+    if let Some((row, &widget)) = rows.iter().zip(&placement.widgets).find(|(row, _)| row.field == field) {
+        field.set(settings, along(track(&placement, row, widget), point));
+    }
+}
+
+/// Draw `rows` on a panel in the middle of the window and return the box it covers.
+pub fn draw(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, rows: &[Row]) -> Rect {
+    let placement = place(layout, rows);
+    let scale = placement.scale;
+    let (top_left, size) = placement.panel;
+    let (background, foreground) = (palette.background, palette.foreground);
+    canvas.blend_rect(top_left, size, background, PANEL_OPACITY);
+    canvas.draw_rect(top_left, size, scale, foreground);
+
+    let text_height = font::text_height(scale);
+    let button_color = draw::blend(background, foreground, BUTTON_SHADE);
+    let corner = |x: usize, y: usize| Point::new(x as isize, y as isize);
+    for (row, &widget) in rows.iter().zip(&placement.widgets) {
+        let text_y = (widget.y0 + (widget.height() - text_height) / 2) as isize;
+        canvas.draw_text(Point::new(placement.label_x, text_y), row.label, scale, foreground);
+
+        match &row.widget {
+            Widget::Slider { value, range } => {
+                let track = track(&placement, row, widget);
+                let middle = (track.y0 + track.height() / 2) as isize;
+                let (left, right) = (track.x0 as isize, track.x1 as isize - 1);
+                canvas.draw_line(Point::new(left, middle), Point::new(right, middle), scale, draw::blend(background, foreground, TRACK_SHADE));
+                // A knob half as wide as it is tall, centered on the value
+                let share = (value - range.start()) as f32 / (range.end() - range.start()).max(1) as f32;
+                let knob_width = widget.height() / 2;
+                let knob_x = left + (share * (right - left) as f32).round() as isize - knob_width as isize / 2;
+                canvas.fill_rect(Point::new(knob_x, widget.y0 as isize), Dimensions { width: knob_width, height: widget.height() }, foreground);
+                let text = value.to_string();
+                let text_x = widget.x1 - font::text_width(&text, scale);
+                canvas.draw_text(Point::new(text_x as isize, text_y), &text, scale, foreground);
+            }
+            Widget::Toggle(on) => {
+                let box_size = Dimensions { width: widget.width(), height: widget.height() };
+                canvas.draw_rect(corner(widget.x0, widget.y0), box_size, scale, foreground);
+                if *on {
+                    let inset = scale * 3;
+                    let inner = Dimensions { width: box_size.width.saturating_sub(inset * 2), height: box_size.height.saturating_sub(inset * 2) };
+                    canvas.fill_rect(corner(widget.x0 + inset, widget.y0 + inset), inner, foreground);
+                }
+            }
+            Widget::Choice { options, selected } => {
+                let (width, gap) = (widget.width() / options.len(), scale * 2);
+                for (i, option) in options.iter().enumerate() {
+                    let x0 = widget.x0 + i * width;
+                    let is_selected = *selected == Some(i);
+                    let button = Dimensions { width: width - gap, height: widget.height() };
+                    canvas.fill_rect(corner(x0, widget.y0), button, if is_selected { foreground } else { button_color });
+                    let text_x = x0 + (button.width.saturating_sub(font::text_width(option, scale))) / 2;
+                    canvas.draw_text(Point::new(text_x as isize, text_y), option, scale, if is_selected { background } else { foreground });
+                }
+            }
+            Widget::Swatches { theme, selected } => {
+                let count = SWATCHES.len() + 1;
+                let width = widget.width() / count;
+                let side = widget.height().min(width * 4 / 5);
+                let colors = std::iter::once(*theme).chain(SWATCHES.iter().map(|swatch| swatch.to_pixel()));
+                for (i, color) in colors.enumerate() {
+                    let top_left = corner(widget.x0 + i * width, widget.y0 + (widget.height() - side) / 2);
+                    let swatch = Dimensions { width: side, height: side };
+                    canvas.fill_rect(top_left, swatch, color);
+                    // Every swatch outlined so one in the background's color still shows, the selected one boldly
+                    let (thickness, outline) = if *selected == Some(i) { (scale * 2, foreground) } else { (scale.div_ceil(2), button_color) };
+                    canvas.draw_rect(top_left, swatch, thickness, outline);
+                }
+            }
+        }
+    }
+
+    // draw_rect's border reaches out half its thickness
+    let end = Point::new(top_left.x + size.width as isize, top_left.y + size.height as isize);
+    Rect::around(&[top_left, end], scale, layout.width, layout.height)
+}
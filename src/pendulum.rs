@@ -0,0 +1,54 @@
+// The metronome arm as a pendulum swinging symmetrically around vertical, pointing up from its
+// pivot or hanging down from it (see `PendulumPivot`).
+//
+// Time is counted in beats: `beats = elapsed / beat_interval`. One beat is one swing from one
+// extreme to the other, so a full period (there and back) is two beats:
+//
+//   beats  0.0      0.5       1.0      1.5       2.0
+//   arm    right -> center -> left  -> center -> right
+//
+// With the default harmonic easing the displacement is cos(PI * beats), simple harmonic motion:
+// the angular velocity is zero at the turning points and largest through vertical, like a real
+// weighted arm. Linear easing moves at a constant speed and bounces off the extremes instead.
+// With either, whole beats land exactly on the extremes, which is where a real metronome ticks,
+// so the click (fired when the whole number of beats goes up) always sounds as the arm turns around.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// How the arm moves between the extremes, toggled with the E key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed, sharp turn at each extreme.
+    Linear,
+    /// Simple harmonic motion, slows into each extreme like a real pendulum.
+    Harmonic,
+}
+
+impl Easing {
+    pub fn toggled(self) -> Self {
+        match self {
+            Easing::Linear => Easing::Harmonic,
+            Easing::Harmonic => Easing::Linear,
+        }
+    }
+
+    /// Position of the arm at `beats` since the start, -1.0 (left extreme) to 1.0 (right extreme).
+    pub fn swing(self, beats: f32) -> f32 {
+        match self {
+            // Triangle wave through the same extremes: 1 at even beats, -1 at odd ones
+            Easing::Linear => 2.0 * (beats.rem_euclid(2.0) - 1.0).abs() - 1.0,
+            Easing::Harmonic => (PI * beats).cos(),
+        }
+    }
+
+    /// Screen angle of the arm (as used by `Point::polar`) for a swing of `arc_degrees` to either side of vertical,
+    /// up from the pivot or down from it when `hanging`. Either way the tip is at the right on even beats.
+    pub fn arm_angle(self, beats: f32, arc_degrees: f32, hanging: bool) -> f32 {
+        let arc = self.swing(beats) * arc_degrees.to_radians();
+        if hanging {
+            FRAC_PI_2 - arc             // DOWN is 90°
+        } else {
+            -FRAC_PI_2 + arc            // UP is -90°
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// The battery saver: whether the computer runs on battery, asked through the battery crate.
+//
+// main.rs polls it from about_to_wait, every POWER_CHECK_INTERVAL at most, and tells the scenes
+// when it changes (Scene::set_on_battery). On battery the watch drops to MIN_FPS and wakes on the
+// second like with power saving, on AC it goes back to the FPS setting.
+// When the state can't be read (no power supply info, a platform the crate doesn't know) the
+// computer counts as on AC, so the FPS setting applies as if the battery saver were off.
+
+use std::time::{Duration, Instant};
+
+use battery::{Manager, State};
+use log::{debug, info, warn};
+
+/// How often to ask. Unplugging takes effect within this, checking costs a few file reads.
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Asks for the power state and remembers the last answer.
+pub struct PowerProbe {
+    manager:    Option<Manager>,    // None if the platform has no battery info, never asked again
+    on_battery: bool,
+    next_check: Instant,
+}
+
+impl PowerProbe {
+    pub fn new() -> Self {
+        let manager = Manager::new()
+            .inspect_err(|e| warn!("Battery status is unavailable, the battery saver keeps the FPS setting: {e}"))
+            .ok();
+        Self { manager, on_battery: false, next_check: Instant::now() }
+    }
+
+    /// Ask again if POWER_CHECK_INTERVAL has passed since last time.
+    /// Returns whether the computer now runs on battery if that changed, `None` otherwise.
+    pub fn poll(&mut self, now: Instant) -> Option<bool> {
+        if now < self.next_check {
+            return None;
+        }
+        self.next_check = now + POWER_CHECK_INTERVAL;
+        let on_battery = self.on_battery();
+        if on_battery == self.on_battery {
+            return None;
+        }
+        self.on_battery = on_battery;
+        info!("Running on {}", if on_battery { "battery, lowering the frame rate" } else { "AC power" });
+        Some(on_battery)
+    }
+
+    /// Whether some battery is discharging. No batteries at all (a desktop) is AC power.
+    fn on_battery(&self) -> bool {
+        let Some(manager) = &self.manager else {
+            return false;
+        };
+        match manager.batteries() {
+            Ok(batteries) => batteries.flatten().any(|battery| battery.state() == State::Discharging),
+            Err(e) => {
+                debug!("Could not read the battery status: {e}");
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// Hot reload of the settings file: a file watcher on the config dir that wakes the event loop
+// when the file changed, so theme, colors, hand lengths and dial position can be edited while the app runs.
+//
+// The directory is watched rather than the file: editors often save by writing a new file and
+// renaming it over the old one, and the file doesn't exist at all before the first save.
+// Saving tends to come as a burst of events (truncate, write, rename), so they are collected
+// until RELOAD_DEBOUNCE passes without another one, then the event loop gets a single wake-up.
+
+use std::fs;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use winit::event_loop::EventLoopProxy;
+
+use crate::config;
+
+// Quiet time after the last change before the file is read
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Start watching the settings file, `proxy` gets an event whenever it changed.
+/// Keep the watcher alive for as long as changes should be picked up, `None` if it couldn't start.
+pub fn watch(proxy: EventLoopProxy<()>) -> Option<RecommendedWatcher> {
+    let path = config::config_path()?;
+    let (dir, file) = (path.parent()?.to_path_buf(), path.file_name()?.to_owned());
+
+    let (changed, changes) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result
+            && event.paths.iter().any(|changed| changed.file_name() == Some(&file))
+        {
+            let _ = changed.send(());
+        }
+    })
+    .map_err(|e| warn!("Could not watch the settings file for changes: {e}"))
+    .ok()?;
+    // The directory only appears with the first save, create it so there's something to watch
+    let watching = fs::create_dir_all(&dir)
+        .map_err(notify::Error::io)
+        .and_then(|()| watcher.watch(&dir, RecursiveMode::NonRecursive));
+    if let Err(e) = watching {
+        warn!("Could not watch '{}' for changes: {e}", dir.display());
+        return None;
+    }
+
+    thread::spawn(move || {
+        while changes.recv().is_ok() {
+            while changes.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+            // The event loop is gone once the app exits
+            if proxy.send_event(()).is_err() {
+                break;
+            }
+        }
+    });
+    Some(watcher)
+}
@@ -0,0 +1,36 @@
+// Rendering without a window: one frame into a plain pixel buffer, optionally saved as a PNG.
+// Used by `--render out.png` for previews, and handy for checking the drawing code by eye.
+// With `--at` the frame shows a set time of day instead of the current one (see time_source.rs),
+// so two renders of the same settings come out the same.
+
+use std::path::Path;
+
+use watchrs::draw::Canvas;
+use crate::hud::Hud;
+use crate::layout::Layout;
+use crate::scene::Scene;
+use crate::theme::ThemePalette;
+use crate::time_source::TimeSource;
+
+/// Draw a whole frame of `scene` at the size of `layout` at the time `time` gives, with the speed readout if `show_hud`.
+pub fn render_frame(layout: &Layout, scene: &mut dyn Scene, palette: &ThemePalette, show_hud: bool, time: &dyn TimeSource) -> Vec<u32> {
+    let mut pixels = vec![0; layout.width * layout.height];
+    let mut canvas = Canvas::new(&mut pixels, layout.dimensions()).expect("buffer is allocated at the layout size");
+    scene.update(time);
+    // A fresh buffer has no history, age 0 makes the scene draw all of it
+    let hud = Hud { text: show_hud.then(|| scene.hud_text()), ..Hud::default() };
+    scene.draw(&mut canvas, layout, palette, 0, &hud);
+    pixels
+}
+
+/// Write a buffer of packed 0x00RRGGBB pixels as an RGBA PNG.
+pub fn save_png(path: &Path, pixels: &[u32], width: usize, height: usize) -> Result<(), image::ImageError> {
+    // Unpack every pixel into its R, G, B bytes, the unused top byte becomes an opaque alpha
+    let rgba = pixels
+        .iter()
+        .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 0xFF])
+        .collect();
+    let image = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .expect("pixel buffer matches width * height");
+    image.save(path)
+}
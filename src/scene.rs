@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+use winit::keyboard::Key;
+
+use watchrs::draw::{Canvas, Point, Rect};
+use crate::config::Settings;
+use crate::hud::Hud;
+use crate::layout::Layout;
+use crate::theme::ThemePalette;
+use crate::time_source::TimeSource;
+
+/// Which scene something is for, where the watch and the metronome look different (title, theme colors).
+/// A single-scene build never constructs the other kind.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SceneKind {
+    Watch,
+    Metronome,
+    Stopwatch,
+    Timer,
+    World,
+}
+
+impl SceneKind {
+    pub fn title(self) -> &'static str {
+        match self {
+            SceneKind::Watch => "WATCHRS - Analog Clock",
+            SceneKind::Metronome => "WATCHRS - Metronome",
+            SceneKind::Stopwatch => "WATCHRS - Stopwatch",
+            SceneKind::Timer => "WATCHRS - Timer",
+            SceneKind::World => "WATCHRS - World Clock",
+        }
+    }
+}
+
+/// One thing the window can show (the watch or the metronome) with its own state, drawing and keys.
+///
+/// The event loop in `main.rs` only owns the window, the surface and what's shared by every scene
+/// (layout, theme, fullscreen), and hands everything else to the active scene.
+pub trait Scene {
+    fn kind(&self) -> SceneKind;
+
+    /// Time between two redraws, `None` to redraw once per frame of the monitor (for smooth motion).
+    fn frame_interval(&self) -> Option<Duration>;
+
+    /// How long until what the scene shows changes next, for a scene that can tell.
+    /// The next frame is then scheduled right at that moment instead of a `frame_interval` later.
+    fn next_change(&self) -> Option<Duration> {
+        None
+    }
+
+    /// When the scene next has to be updated for something that isn't drawn (the metronome's click),
+    /// `None` if nothing is due. The app updates it right then, also when no frame is due until later.
+    fn next_click(&self) -> Option<Instant> {
+        None
+    }
+
+    /// The computer went on battery or back on AC power, with the battery saver on (see power.rs).
+    #[cfg(feature = "battery-saver")]
+    fn set_on_battery(&mut self, _on_battery: bool) {}
+
+    /// The settings panel changed `settings` (see panel.rs), take over whatever the scene keeps of them.
+    /// They hold the scene's own state from `save` for everything the panel didn't change.
+    #[cfg(feature = "gui")]
+    fn apply_settings(&mut self, _settings: &Settings) {}
+
+    /// The scene runs again after another one was shown (Tab in a `combined` build) or after a pause.
+    /// The buffers now hold the other scene's frames, and time went on without `update` being called.
+    fn activate(&mut self, _now: Instant) {}
+
+    /// Start over from the present (the R key): live time for the watch, beat one for the metronome.
+    fn reset(&mut self, now: Instant);
+
+    /// Advance the state to the time `time` gives: read the clock, count beats.
+    fn update(&mut self, time: &dyn TimeSource);
+
+    /// Draw the current state into `canvas`, which is sized like `layout`.
+    ///
+    /// `buffer_age` is how many frames ago the canvas content was last presented (0 if unknown),
+    /// a scene that keeps track of what it drew can repaint only the parts that changed.
+    /// Returns those changed regions, `None` when the whole canvas may have changed.
+    /// `hud` is what to draw on top with `Hud::draw`: the speed readout and the on-screen buttons, while shown.
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>>;
+
+    /// The current speed for the on-screen readout. By default the redraw rate, "0 FPS" for scenes
+    /// that follow the monitor (those should say what speed means for them instead).
+    fn hud_text(&self) -> String {
+        let fps = self.frame_interval().map_or(0.0, |interval| 1.0 / interval.as_secs_f32());
+        format!("{} FPS", fps.round())
+    }
+
+    /// A word or two to show big on top for a moment after a key changed something (the watch's face
+    /// style), `None` when there's nothing to say. Asked on every frame, gone on the first one after it's over.
+    fn notice(&self) -> Option<String> {
+        None
+    }
+
+    /// Handle a pressed key the app itself doesn't use.
+    fn key(&mut self, key: &Key);
+
+    /// Faster (positive `steps`) or slower, one step per +/- key press or scroll notch.
+    fn change_speed(&mut self, steps: i32);
+
+    /// The cursor moved to `point` (clamped to the window). Returns true if that needs a redraw.
+    fn cursor_moved(&mut self, _layout: &Layout, _point: Point) -> bool {
+        false
+    }
+
+    /// The left mouse button was pressed or released at `point`.
+    /// Returns true if the scene used it (grabbed a hand), false leaves a press to the app.
+    fn mouse_button(&mut self, _layout: &Layout, _point: Point, _pressed: bool) -> bool {
+        false
+    }
+
+    /// Copy whatever should survive a restart into `settings`.
+    fn save(&self, settings: &mut Settings);
+}
@@ -0,0 +1,187 @@
+// The settings as one line of text to pass on: the X key prints the settings a window shows, and
+// `--config-string` starts with them, the same look on another computer.
+//
+// The string is "watchrs1:" and the settings file's TOML in URL-safe base64 without padding, so it
+// survives a chat message, a URL and a shell without quoting. Only the values that differ from the
+// defaults go in, which keeps it short, and leaves out settings added later: those take their
+// defaults when it's read. A string that doesn't decode or parse is turned down with what's wrong.
+
+use std::fmt;
+
+use toml::Table;
+
+use crate::config::Settings;
+
+// Marks the string and its format, a new format gets a new number
+const PREFIX: &str = "watchrs1:";
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Why a config string was turned down.
+pub enum ShareError {
+    Prefix,                     // not a config string, or one of a later format
+    Character(char, usize),     // a character base64 doesn't use, and where it is
+    Truncated,                  // a character short
+    Text,                       // the bytes aren't UTF-8 text
+    Settings(toml::de::Error),  // the text isn't settings
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::Prefix => write!(f, "it doesn't start with '{PREFIX}', the X key prints one that does"),
+            ShareError::Character(c, at) => write!(f, "'{c}' at character {at} isn't part of a config string"),
+            ShareError::Truncated => write!(f, "it's cut off at the end"),
+            ShareError::Text => write!(f, "it doesn't hold any settings"),
+            ShareError::Settings(e) => write!(f, "the settings in it are invalid: {}", e.message()),
+        }
+    }
+}
+
+/// `settings` as a config string.
+pub fn export(settings: &Settings) -> String {
+    let mut table = Table::try_from(settings).expect("settings serialize to TOML");
+    let defaults = Table::try_from(Settings::default()).expect("settings serialize to TOML");
+    strip_defaults(&mut table, &defaults);
+    format!("{PREFIX}{}", encode(table.to_string().as_bytes()))
+}
+
+/// The settings in a config string from `export`. Surrounding whitespace is ignored.
+pub fn import(text: &str) -> Result<Settings, ShareError> {
+    let text = text.trim();
+    let encoded = text.strip_prefix(PREFIX).ok_or(ShareError::Prefix)?;
+    let bytes = decode(encoded, PREFIX.len())?;
+    let toml = String::from_utf8(bytes).map_err(|_| ShareError::Text)?;
+    toml::from_str(&toml).map_err(ShareError::Settings)
+}
+
+/// Drop what `table` has the same as `defaults`, in nested tables too (an emptied one goes).
+fn strip_defaults(table: &mut Table, defaults: &Table) {
+    table.retain(|key, value| match (value, defaults.get(key)) {
+        (toml::Value::Table(nested), Some(toml::Value::Table(default))) => {
+            strip_defaults(nested, default);
+            !nested.is_empty()
+        }
+        (value, default) => default != Some(value),
+    });
+}
+
+/// URL-safe base64 of `bytes`, without padding.
+fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        // Up to 3 bytes into the top of 24 bits, out as one character per 6 of them used
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3f] as char);
+        }
+    }
+    text
+}
+
+/// The bytes in unpadded URL-safe base64 `text`. `offset` is added to the position of a bad
+/// character, to count it from the start of the whole string.
+fn decode(text: &str, offset: usize) -> Result<Vec<u8>, ShareError> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for (i, c) in text.chars().enumerate() {
+        let value = ALPHABET
+            .iter()
+            .position(|&letter| letter as char == c)
+            .ok_or(ShareError::Character(c, offset + i + 1))?;
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    // 2 or 4 bits left over are the padding of the last byte, 6 are a character without a byte
+    if count == 6 {
+        return Err(ShareError::Truncated);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_of(bytes: &[u8]) -> String {
+        format!("{PREFIX}{}", encode(bytes))
+    }
+
+    #[test]
+    fn settings_come_back_from_their_config_string() {
+        let mut settings = Settings {
+            bpm: 96,
+            world_zones: vec!["Asia/Tokyo".to_string(), "America/Lima".to_string()],
+            ..Settings::default()
+        };
+        settings.hand_lengths.minute = 0.625;
+        settings.complications.moon_phase = true;
+        let text = export(&settings);
+        assert!(text.starts_with(PREFIX), "{text}");
+
+        let Ok(back) = import(&format!("  {text}\n")) else { panic!("{text} didn't import") };
+        assert_eq!(back.bpm, 96);
+        assert_eq!(back.world_zones, settings.world_zones);
+        assert_eq!(back.hand_lengths, settings.hand_lengths);
+        assert_eq!(back.complications, settings.complications);
+        assert_eq!(export(&back), text);
+    }
+
+    #[test]
+    fn only_the_changed_values_go_in() {
+        assert_eq!(export(&Settings::default()), PREFIX);
+
+        // A nested table keeps only its own changed values
+        let mut settings = Settings::default();
+        settings.hand_lengths.minute = 0.625;
+        let text = export(&settings);
+        let bytes = decode(text.strip_prefix(PREFIX).unwrap(), 0).ok().unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap().trim(), "[hand_lengths]\nminute = 0.625");
+    }
+
+    #[test]
+    fn base64_of_every_length_left_over() {
+        // Lengths of 0, 1 and 2 mod 3: whole groups of 4 characters, then 2 or 3 for the rest
+        for (bytes, text) in [(&b""[..], ""), (b"f", "Zg"), (b"fo", "Zm8"), (b"foo", "Zm9v"), (b"foob", "Zm9vYg"), (b"fooba", "Zm9vYmE"), (b"foobar", "Zm9vYmFy")] {
+            assert_eq!(encode(bytes), text);
+            assert_eq!(decode(text, 0).ok().as_deref(), Some(bytes), "{text}");
+        }
+        // The characters only the URL-safe alphabet has
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+    }
+
+    #[test]
+    fn a_string_without_the_prefix_is_turned_down() {
+        for text in ["", "Zm9v", "watchrs2:Zm9v", "WATCHRS1:Zm9v"] {
+            assert!(matches!(import(text), Err(ShareError::Prefix)), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn a_stray_character_is_turned_down_with_its_position() {
+        // Counted from 1 over the whole string, the prefix is 9 characters
+        assert!(matches!(import("watchrs1:Zm9v!Zm9v"), Err(ShareError::Character('!', 14))));
+        assert!(matches!(import("watchrs1:Zm+v"), Err(ShareError::Character('+', 12))));
+    }
+
+    #[test]
+    fn a_one_character_tail_is_turned_down() {
+        assert!(matches!(import("watchrs1:Zm9vY"), Err(ShareError::Truncated)));
+        assert!(matches!(import("watchrs1:Z"), Err(ShareError::Truncated)));
+    }
+
+    #[test]
+    fn bytes_that_arent_text_are_turned_down() {
+        assert!(matches!(import(&string_of(&[0xff, 0xfe, 0xfd])), Err(ShareError::Text)));
+    }
+
+    #[test]
+    fn text_that_isnt_settings_is_turned_down() {
+        assert!(matches!(import(&string_of(b"bpm = \"fast\"")), Err(ShareError::Settings(_))));
+        assert!(matches!(import(&string_of(b"[hand_lengths")), Err(ShareError::Settings(_))));
+    }
+}
@@ -0,0 +1,167 @@
+// The stopwatch scene, drawn on the watch face: the long hand sweeps the seconds, the shorter one
+// counts minutes (one turn per hour), and the readout shows the elapsed time and the last lap.
+//
+// Elapsed time is the time banked by earlier runs plus the time since the current run started,
+// so stopping and starting again carries on from where it stopped.
+// The face is drawn once into a background snapshot like the watch does, but the stopwatch
+// copies all of it back each frame instead of tracking dirty rectangles: a whole-buffer copy is
+// a fraction of a millisecond and the two readout lines change size as the digits do.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::time::{Duration, Instant};
+
+use log::info;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::config::{Bezel, Settings};
+use watchrs::draw::{Canvas, Point, Rect};
+use watchrs::font;
+use crate::hud::Hud;
+use crate::layout::Layout;
+use crate::scene::{Scene, SceneKind};
+use crate::theme::ThemePalette;
+use crate::time_source::TimeSource;
+use crate::watch::{self, MAX_FPS};
+
+/// The stopwatch with its laps.
+pub struct StopwatchScene {
+    running_since: Option<Instant>, // start of the current run, None while stopped
+    banked:     Duration,           // elapsed in the runs before the current one
+    elapsed:    Duration,           // total elapsed at the last update
+    laps:       Vec<Duration>,      // elapsed time at each L press, oldest first
+    background: Vec<u32>,           // the face drawn once, empty when it needs a redraw
+    background_look: Option<(usize, usize, ThemePalette)>, // size and colors the background was drawn with
+}
+
+impl StopwatchScene {
+    pub fn new() -> Self {
+        Self {
+            running_since: None,
+            banked: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            laps: Vec::new(),
+            background: Vec::new(),
+            background_look: None,
+        }
+    }
+
+    fn start_stop(&mut self) {
+        let now = Instant::now();
+        match self.running_since.take() {
+            Some(since) => {
+                self.banked += now - since;
+                info!("Stopwatch stopped at {}", format_elapsed(self.banked));
+            }
+            None => {
+                self.running_since = Some(now);
+                info!("Stopwatch started");
+            }
+        }
+    }
+
+    fn lap(&mut self) {
+        // Laps only mean something while the clock runs
+        let Some(since) = self.running_since else {
+            return;
+        };
+        let lap = self.banked + since.elapsed();
+        self.laps.push(lap);
+        info!("Lap {}: {}", self.laps.len(), format_elapsed(lap));
+    }
+}
+
+impl Scene for StopwatchScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Stopwatch
+    }
+
+    fn frame_interval(&self) -> Option<Duration> {
+        // The readout shows tenths, MAX_FPS redraws keep them and the sweeping hand smooth
+        Some(Duration::from_secs_f32(1.0 / MAX_FPS as f32))
+    }
+
+    fn reset(&mut self, _now: Instant) {
+        // Back to zero and stopped, the laps go too
+        self.running_since = None;
+        self.banked = Duration::ZERO;
+        self.elapsed = Duration::ZERO;
+        self.laps.clear();
+        info!("Stopwatch reset");
+    }
+
+    fn update(&mut self, time: &dyn TimeSource) {
+        let now = time.instant();
+        self.elapsed = self.banked + self.running_since.map_or(Duration::ZERO, |since| now - since);
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, _buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>> {
+        let look = Some((layout.width, layout.height, *palette));
+        if self.background.is_empty() || self.background_look != look {
+            self.background = vec![0; canvas.buf.len()];
+            let mut background = Canvas::new(&mut self.background, layout.dimensions())
+                .expect("background is allocated at the layout size");
+            watch::draw_face(&mut background, layout, palette, None, None, Bezel::Flat, false);
+            self.background_look = look;
+        }
+        canvas.buf.copy_from_slice(&self.background);
+        let last_lap = self.laps.last().map(|&lap| (self.laps.len(), lap));
+        draw_hands(canvas, layout, palette, self.elapsed, last_lap);
+        hud.draw(canvas, layout, palette);
+        None
+    }
+
+    fn key(&mut self, key: &Key) {
+        match key {
+            Key::Named(NamedKey::Space) => self.start_stop(),
+            Key::Character(s) if s == "l" => self.lap(),
+            _ => {}
+        }
+    }
+
+    // Nothing to speed up
+    fn change_speed(&mut self, _steps: i32) {}
+
+    // Nothing worth keeping, a restart starts from zero
+    fn save(&self, _settings: &mut Settings) {}
+}
+
+/// Elapsed time as "MM:SS.t", minutes keep counting past 59.
+fn format_elapsed(elapsed: Duration) -> String {
+    let tenths = elapsed.as_millis() / 100;
+    format!("{:02}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Readout, hands and hub for `elapsed`, with the last lap (number, time) under the readout if there is one.
+fn draw_hands(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, elapsed: Duration, last_lap: Option<(usize, Duration)>) {
+    let watch = layout.watch;
+    let center = layout.center;
+    let foreground = palette.foreground;
+
+    // Readout and last lap below the center, under the hands
+    let line_gap = font::text_height(watch.readout_scale) as isize * 3 / 2;
+    let lap_text = last_lap.map(|(number, lap)| format!("L{number} {}", format_elapsed(lap)));
+    let lines = [Some(format_elapsed(elapsed)), lap_text];
+    for (i, text) in lines.iter().enumerate() {
+        let Some(text) = text else {
+            continue;
+        };
+        let text_width = font::text_width(text, watch.readout_scale);
+        let origin = Point::new(center.x - (text_width / 2) as isize, watch.readout_y + i as isize * line_gap);
+        canvas.draw_text(origin, text, watch.readout_scale, foreground);
+    }
+
+    // The seconds hand sweeps once a minute, the minute hand once an hour
+    let seconds = elapsed.as_secs_f32();
+    let up = -FRAC_PI_2;            // 12 o'clock is -90°
+    let seconds_angle = up + seconds * (TAU / 60.0);
+    let minute_angle = up + seconds / 60.0 * (TAU / 60.0);
+
+    let hands = [
+        (minute_angle, watch.minute_hand, palette.minute_hand),
+        (seconds_angle, watch.seconds_hand, foreground),
+    ];
+    for (angle, (length, thick), color) in hands {
+        watch::draw_hand(canvas, center, center.polar(angle, length), thick, color);
+    }
+    canvas.draw_filled_circle(center, watch.hub_radius, palette.hub);
+}
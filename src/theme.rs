@@ -0,0 +1,196 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Rgb;
+use crate::scene::SceneKind;
+use watchrs::draw::{self, color_rgb, Canvas};
+
+// Darker shades of the foreground for the watch hands, used when the foreground comes from the settings file
+const MINUTE_HAND_SHADE: f32 = 0.8;
+const HOUR_HAND_SHADE: f32 = 0.6;
+const HUB_SHADE: f32 = 0.4;
+// How far the metronome housing is mixed from the background towards the foreground, for custom colors
+const HOUSING_MIX: f32 = 0.2;
+/// How long a theme change takes to fade from the old colors to the new ones.
+const THEME_FADE: Duration = Duration::from_millis(300);
+
+/// Preset color schemes, cycled with the C key and remembered in the settings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Pure black and white only, for accessibility.
+    HighContrast,
+}
+
+/// Every color something draws, as canvas pixels.
+/// Shared by both scenes, each only reads the colors it draws.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ThemePalette {
+    pub background:  u32,
+    pub foreground:  u32,   // frame, dial, text and metronome arm
+    pub seconds_hand: u32,  // red on a classic watch, set apart from the other hands
+    pub minute_hand: u32,
+    pub hour_hand:   u32,
+    pub hub:         u32,
+    pub accent:      u32,   // metronome downbeat
+    pub housing:     u32,   // metronome body, a faint foreground over the background so the arm stands out
+    pub background_bottom: Option<u32>, // background fades to this at the bottom, None for a flat one
+}
+
+impl Theme {
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Dark => Theme::Light,
+            Theme::Light => Theme::HighContrast,
+            Theme::HighContrast => Theme::Dark,
+        }
+    }
+
+    /// Colors of this theme for `scene`, the scenes only differ in the foreground.
+    /// The stopwatch and the timer are watch faces, so they get the watch colors.
+    pub fn colors(&self, scene: SceneKind) -> ThemePalette {
+        match self {
+            Theme::Dark => ThemePalette {
+                background:  color_rgb(75, 95, 100),
+                foreground:  match scene {
+                    SceneKind::Watch | SceneKind::Stopwatch | SceneKind::Timer | SceneKind::World => color_rgb(0, 200, 255),
+                    SceneKind::Metronome => color_rgb(0, 255, 30),
+                },
+                seconds_hand: color_rgb(255, 80, 70),
+                minute_hand: color_rgb(0, 160, 205),
+                hour_hand:   color_rgb(0, 120, 155),
+                hub:         color_rgb(0, 80, 105),
+                accent:      color_rgb(200, 255, 210),
+                housing:     match scene {
+                    SceneKind::Watch | SceneKind::Stopwatch | SceneKind::Timer | SceneKind::World => color_rgb(60, 116, 131),
+                    SceneKind::Metronome => color_rgb(60, 127, 86),
+                },
+                background_bottom: None,
+            },
+            Theme::Light => ThemePalette {
+                background:  color_rgb(235, 235, 228),
+                foreground:  match scene {
+                    SceneKind::Watch | SceneKind::Stopwatch | SceneKind::Timer | SceneKind::World => color_rgb(20, 70, 110),
+                    SceneKind::Metronome => color_rgb(20, 110, 40),
+                },
+                seconds_hand: color_rgb(200, 40, 30),
+                minute_hand: color_rgb(50, 60, 70),
+                hour_hand:   color_rgb(25, 30, 35),
+                hub:         color_rgb(10, 10, 10),
+                accent:      color_rgb(220, 80, 40),
+                housing:     match scene {
+                    SceneKind::Watch | SceneKind::Stopwatch | SceneKind::Timer | SceneKind::World => color_rgb(192, 202, 204),
+                    SceneKind::Metronome => color_rgb(192, 210, 190),
+                },
+                background_bottom: None,
+            },
+            Theme::HighContrast => ThemePalette {
+                background:  color_rgb(0, 0, 0),
+                foreground:  color_rgb(255, 255, 255),
+                seconds_hand: color_rgb(255, 255, 255),
+                minute_hand: color_rgb(255, 255, 255),
+                hour_hand:   color_rgb(255, 255, 255),
+                hub:         color_rgb(255, 255, 255),
+                // The accent lights up the pivot over the white arm, so it has to be dark to show
+                accent:      color_rgb(0, 0, 0),
+                // No grays here, the body is only its white outline
+                housing:     color_rgb(0, 0, 0),
+                background_bottom: None,
+            },
+        }
+    }
+}
+
+impl ThemePalette {
+    /// Apply colors set in the settings file on top of the theme.
+    /// A custom foreground also replaces the hour and minute hand colors, with darker shades of it,
+    /// and either custom color brings the housing in line with them. The seconds hand keeps the
+    /// theme's color unless `seconds_hand` is set.
+    pub fn with_overrides(mut self, background: Option<Rgb>, foreground: Option<Rgb>, background_bottom: Option<Rgb>, seconds_hand: Option<Rgb>) -> Self {
+        self.background_bottom = background_bottom.map(Rgb::to_pixel);
+        if let Some(seconds_hand) = seconds_hand {
+            self.seconds_hand = seconds_hand.to_pixel();
+        }
+        if let Some(background) = background {
+            self.background = background.to_pixel();
+        }
+        if let Some(foreground) = foreground {
+            let foreground = foreground.to_pixel();
+            self.foreground = foreground;
+            self.minute_hand = draw::shade(foreground, MINUTE_HAND_SHADE);
+            self.hour_hand = draw::shade(foreground, HOUR_HAND_SHADE);
+            self.hub = draw::shade(foreground, HUB_SHADE);
+        }
+        if background.is_some() || foreground.is_some() {
+            self.housing = draw::blend(self.background, self.foreground, HOUSING_MIX);
+        }
+        self
+    }
+
+    /// Every color `t` of the way (0.0 to 1.0) from this palette to `to`.
+    /// A flat background fades like a gradient with the same color at the bottom.
+    pub fn lerp(self, to: ThemePalette, t: f32) -> Self {
+        let mix = |from: u32, to: u32| draw::lerp_color(from, to, t);
+        let background_bottom = match (self.background_bottom, to.background_bottom) {
+            (None, None) => None,
+            (from, to_bottom) => Some(mix(from.unwrap_or(self.background), to_bottom.unwrap_or(to.background))),
+        };
+        Self {
+            background: mix(self.background, to.background),
+            foreground: mix(self.foreground, to.foreground),
+            seconds_hand: mix(self.seconds_hand, to.seconds_hand),
+            minute_hand: mix(self.minute_hand, to.minute_hand),
+            hour_hand: mix(self.hour_hand, to.hour_hand),
+            hub: mix(self.hub, to.hub),
+            accent: mix(self.accent, to.accent),
+            housing: mix(self.housing, to.housing),
+            background_bottom,
+        }
+    }
+
+    /// Every color but the background `amount` of the way (0.0 to 1.0) towards the background.
+    pub fn faded(mut self, amount: f32) -> Self {
+        let background = self.background;
+        for color in [&mut self.foreground, &mut self.seconds_hand, &mut self.minute_hand, &mut self.hour_hand, &mut self.hub, &mut self.accent, &mut self.housing] {
+            *color = draw::blend(*color, background, amount);
+        }
+        self.background_bottom = self.background_bottom.map(|bottom| draw::blend(bottom, background, amount));
+        self
+    }
+
+    /// Paint the whole canvas with the background, flat or as a gradient down to `background_bottom`.
+    pub fn fill_background(&self, canvas: &mut Canvas) {
+        match self.background_bottom {
+            Some(bottom) => canvas.fill_vertical_gradient(self.background, bottom),
+            None => canvas.clear(self.background),
+        }
+    }
+}
+
+/// A theme change in progress, fading from the colors shown before to the new theme's over THEME_FADE.
+pub struct ThemeTransition {
+    from:   ThemePalette,
+    to:     ThemePalette,
+    start:  Instant,
+}
+
+impl ThemeTransition {
+    pub fn new(from: ThemePalette, to: ThemePalette) -> Self {
+        Self { from, to, start: Instant::now() }
+    }
+
+    /// The colors to draw with at `now`, `to` once the fade is over.
+    pub fn palette_at(&self, now: Instant) -> ThemePalette {
+        let t = now.saturating_duration_since(self.start).as_secs_f32() / THEME_FADE.as_secs_f32();
+        self.from.lerp(self.to, t.min(1.0))
+    }
+
+    pub fn done(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.start) >= THEME_FADE
+    }
+}
@@ -0,0 +1,91 @@
+// Where the scenes get "now" from, handed to `Scene::update` every frame.
+//
+// There are two nows: the monotonic `Instant` that beats and elapsed times are counted in (the
+// metronome, the stopwatch, the timer) and the wall-clock time the watch shows. The window runs on
+// the system clock. A `FakeClock` stays at the time it was set to, so `--render out.png --at 10:10`
+// draws the hands at that time of day, the same on every run. The tests move one along by hand.
+// With --ntp the system clock's wall time is corrected by what the NTP server said (see ntp.rs).
+// Everything that reads the wall time goes through `SystemClock` for that.
+
+#[cfg(feature = "ntp")]
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// How far the system clock is off, set once at startup
+#[cfg(feature = "ntp")]
+static CORRECTION: OnceLock<TimeDelta> = OnceLock::new();
+
+pub trait TimeSource {
+    /// Monotonic time, for how long since something happened.
+    fn instant(&self) -> Instant;
+
+    /// Wall-clock time in UTC, the scenes convert it to the zone they show.
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    fn wall(&self) -> DateTime<Utc>;
+}
+
+/// The computer's clocks.
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> DateTime<Utc> {
+        Utc::now() + correction()
+    }
+}
+
+/// Correct the system clock's wall time by `correction` from now on. Only the first one counts.
+#[cfg(feature = "ntp")]
+pub fn set_correction(correction: TimeDelta) {
+    let _ = CORRECTION.set(correction);
+}
+
+/// What `SystemClock` adds to the system time, zero unless --ntp set it.
+#[cfg(feature = "ntp")]
+fn correction() -> TimeDelta {
+    CORRECTION.get().copied().unwrap_or_default()
+}
+
+#[cfg(not(feature = "ntp"))]
+fn correction() -> TimeDelta {
+    TimeDelta::zero()
+}
+
+/// A clock that doesn't move: both times are those it was set to.
+pub struct FakeClock {
+    instant: Instant,
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    wall:    DateTime<Utc>,
+}
+
+impl FakeClock {
+    /// Stopped at `wall`. The monotonic time is taken when it's set, nothing is counted from
+    /// before that (a scene's start) anyway.
+    pub fn new(wall: DateTime<Utc>) -> Self {
+        Self { instant: Instant::now(), wall }
+    }
+
+    /// Move both times on by `by`, for tests that step a scene through time.
+    /// Only the metronome's tests do that so far.
+    #[cfg(test)]
+    #[cfg_attr(not(feature = "metronome"), allow(dead_code))]
+    pub fn advance(&mut self, by: std::time::Duration) {
+        self.instant += by;
+        self.wall += by;
+    }
+}
+
+impl TimeSource for FakeClock {
+    fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    fn wall(&self) -> DateTime<Utc> {
+        self.wall
+    }
+}
@@ -0,0 +1,207 @@
+// The countdown timer scene, drawn on the watch face: the hands show the time left, so they run
+// backwards and meet at 12 when it's up. The readout shows the time left as MM:SS.
+//
+// At zero the frame pulses between the foreground color and black for ALERT_LENGTH, with a click
+// on every pulse when built with sound, then the timer sits at 00:00 until it is reset.
+// Like the stopwatch, time left is the set duration minus the time banked by earlier runs and the
+// time since the current run started, so Space pauses and resumes it.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::time::{Duration, Instant};
+
+use log::info;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::audio::Clicker;
+use crate::config::{Bezel, Settings};
+use watchrs::draw::{self, Canvas, Point, Rect};
+use watchrs::font;
+use crate::hud::Hud;
+use crate::layout::Layout;
+use crate::scene::{Scene, SceneKind};
+use crate::theme::ThemePalette;
+use crate::time_source::TimeSource;
+use crate::watch::{self, MAX_FPS};
+
+/// Shortest and longest settable duration in seconds, the readout has two digits of minutes
+pub const MIN_TIMER: u32 = 30;
+pub const MAX_TIMER: u32 = 99 * 60;
+const TIMER_STEP: u32 = 30;     // per +/- key press or scroll notch
+// The alert at zero: how long it lasts and how fast the frame pulses
+const ALERT_LENGTH: Duration = Duration::from_secs(5);
+const ALERT_PULSES_PER_SECOND: f32 = 2.0;
+// Nothing moves while stopped, a redraw a second is plenty (keys redraw right away)
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The countdown timer.
+pub struct TimerScene {
+    duration:   Duration,           // what it counts down from
+    running_since: Option<Instant>, // start of the current run, None while stopped
+    banked:     Duration,           // elapsed in the runs before the current one
+    remaining:  Duration,           // time left at the last update
+    finished_at: Option<Instant>,   // when it reached zero, for the alert
+    alert:      Option<f32>,        // frame brightness while the alert pulses, None otherwise
+    pulses:     u32,                // alert pulses clicked so far
+    clicker:    Clicker,
+    background: Vec<u32>,           // the face drawn once, empty when it needs a redraw
+    background_look: Option<(usize, usize, ThemePalette)>, // size and colors the background was drawn with
+}
+
+impl TimerScene {
+    pub fn new(seconds: u32, clicker: Clicker) -> Self {
+        let duration = Duration::from_secs(seconds as u64);
+        Self {
+            duration,
+            running_since: None,
+            banked: Duration::ZERO,
+            remaining: duration,
+            finished_at: None,
+            alert: None,
+            pulses: 0,
+            clicker,
+            background: Vec::new(),
+            background_look: None,
+        }
+    }
+
+    fn start_stop(&mut self) {
+        let now = Instant::now();
+        match self.running_since.take() {
+            Some(since) => {
+                self.banked += now - since;
+                info!("Timer stopped with {} left", format_remaining(self.remaining));
+            }
+            // Starting a finished timer starts it over
+            None if self.finished_at.is_some() => {
+                self.reset(now);
+                self.running_since = Some(now);
+                info!("Timer restarted");
+            }
+            None => {
+                self.running_since = Some(now);
+                info!("Timer started");
+            }
+        }
+    }
+}
+
+impl Scene for TimerScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Timer
+    }
+
+    fn frame_interval(&self) -> Option<Duration> {
+        if self.running_since.is_some() || self.alert.is_some() {
+            Some(Duration::from_secs_f32(1.0 / MAX_FPS as f32))
+        } else {
+            Some(IDLE_INTERVAL)
+        }
+    }
+
+    fn reset(&mut self, _now: Instant) {
+        // Stopped with the whole duration left
+        self.running_since = None;
+        self.banked = Duration::ZERO;
+        self.remaining = self.duration;
+        self.finished_at = None;
+        self.alert = None;
+        self.pulses = 0;
+        info!("Timer reset to {}", format_remaining(self.duration));
+    }
+
+    fn update(&mut self, time: &dyn TimeSource) {
+        let now = time.instant();
+        let elapsed = self.banked + self.running_since.map_or(Duration::ZERO, |since| now - since);
+        self.remaining = self.duration.saturating_sub(elapsed);
+
+        if self.remaining.is_zero() && self.finished_at.is_none() {
+            self.running_since = None;
+            self.finished_at = Some(now);
+            info!("Time's up");
+        }
+
+        // Pulse the frame from full brightness to black and back, clicking at the start of each pulse
+        self.alert = self.finished_at.map(|at| now - at).filter(|&since| since < ALERT_LENGTH).map(|since| {
+            let pulses = since.as_secs_f32() * ALERT_PULSES_PER_SECOND;
+            if pulses as u32 >= self.pulses {
+                self.pulses = pulses as u32 + 1;
+                self.clicker.click(true);
+            }
+            0.5 + 0.5 * (pulses * TAU).cos()
+        });
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, _buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>> {
+        let look = Some((layout.width, layout.height, *palette));
+        if self.background.is_empty() || self.background_look != look {
+            self.background = vec![0; canvas.buf.len()];
+            let mut background = Canvas::new(&mut self.background, layout.dimensions())
+                .expect("background is allocated at the layout size");
+            watch::draw_face(&mut background, layout, palette, None, None, Bezel::Flat, false);
+            self.background_look = look;
+        }
+        canvas.buf.copy_from_slice(&self.background);
+
+        if let Some(brightness) = self.alert {
+            canvas.draw_frame(layout.frame_padding, layout.thick, draw::shade(palette.foreground, brightness));
+        }
+        draw_hands(canvas, layout, palette, self.remaining);
+        hud.draw(canvas, layout, palette);
+        None
+    }
+
+    fn key(&mut self, key: &Key) {
+        if let Key::Named(NamedKey::Space) = key {
+            self.start_stop();
+        }
+    }
+
+    /// Lengthen (positive `steps`) or shorten the duration by TIMER_STEP per step, within MIN_TIMER..=MAX_TIMER.
+    /// Also changes a timer that is already counting down, the time run so far stays counted.
+    fn change_speed(&mut self, steps: i32) {
+        let seconds = self.duration.as_secs() as i64 + steps as i64 * TIMER_STEP as i64;
+        self.duration = Duration::from_secs(seconds.clamp(MIN_TIMER as i64, MAX_TIMER as i64) as u64);
+        info!("Timer set to {}", format_remaining(self.duration));
+        if self.running_since.is_none() && self.finished_at.is_none() {
+            self.remaining = self.duration.saturating_sub(self.banked);
+        }
+    }
+
+    fn save(&self, settings: &mut Settings) {
+        settings.timer = self.duration.as_secs() as u32;
+    }
+}
+
+/// Time left as "MM:SS", rounded up so it only shows 00:00 once it's really over.
+fn format_remaining(remaining: Duration) -> String {
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Readout, hands and hub for `remaining`.
+fn draw_hands(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, remaining: Duration) {
+    let watch = layout.watch;
+    let center = layout.center;
+    let foreground = palette.foreground;
+
+    // Readout below the center, under the hands
+    let text = format_remaining(remaining);
+    let text_width = font::text_width(&text, watch.readout_scale);
+    let origin = Point::new(center.x - (text_width / 2) as isize, watch.readout_y);
+    canvas.draw_text(origin, &text, watch.readout_scale, foreground);
+
+    // Same hands as the stopwatch, but for the time left: both turn back towards 12 as it runs out
+    let seconds = remaining.as_secs_f32();
+    let up = -FRAC_PI_2;            // 12 o'clock is -90°
+    let seconds_angle = up + seconds * (TAU / 60.0);
+    let minute_angle = up + seconds / 60.0 * (TAU / 60.0);
+
+    let hands = [
+        (minute_angle, watch.minute_hand, palette.minute_hand),
+        (seconds_angle, watch.seconds_hand, foreground),
+    ];
+    for (angle, (length, thick), color) in hands {
+        watch::draw_hand(canvas, center, center.polar(angle, length), thick, color);
+    }
+    canvas.draw_filled_circle(center, watch.hub_radius, palette.hub);
+}
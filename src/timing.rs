@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+/// How often the frame times are printed.
+const REPORT_EVERY: Duration = Duration::from_secs(1);
+
+/// The first frame time after `now` on the schedule of one frame every `interval` through `start`
+/// (any frame time of that schedule, the last one scheduled is handy). `start` itself if it's
+/// still ahead. An interval rather than an FPS, the metronome's frames come at the monitor's
+/// refresh interval, which isn't a whole number of frames per second.
+///
+/// Frames that were due while nothing ran (a long stall, the machine asleep) are skipped in one step
+/// instead of being counted off one by one, and the result stays on the schedule: frames keep
+/// falling at `start + n * interval` however late the call comes, so nothing drifts.
+pub fn next_frame_instant(start: Instant, now: Instant, interval: Duration) -> Instant {
+    if now < start {
+        return start;
+    }
+    if interval.is_zero() {
+        return now;
+    }
+    let intervals = (now - start).as_nanos() / interval.as_nanos() + 1;
+    start + Duration::from_nanos((intervals * interval.as_nanos()) as u64)
+}
+
+/// Debug aid for `--stats` (always on with the "frame-timing" feature): measures how long each
+/// redraw takes (clear, drawing and present) and prints min/avg/max to stderr once a second.
+pub struct FrameTimer {
+    total:       Duration,
+    min:         Duration,
+    max:         Duration,
+    frames:      u32,
+    last_report: Instant,
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self { total: Duration::ZERO, min: Duration::MAX, max: Duration::ZERO, frames: 0, last_report: Instant::now() }
+    }
+
+    pub fn record(&mut self, frame_time: Duration) {
+        self.total += frame_time;
+        self.min = self.min.min(frame_time);
+        self.max = self.max.max(frame_time);
+        self.frames += 1;
+
+        if self.last_report.elapsed() >= REPORT_EVERY {
+            let average = self.total / self.frames;
+            let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+            eprintln!(
+                "Frame time: {:.3} / {:.3} / {:.3} ms min/avg/max over {} frames",
+                ms(self.min), ms(average), ms(self.max), self.frames
+            );
+            *self = Self::new();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn next_frame_is_the_start_while_it_is_ahead() {
+        let start = Instant::now() + Duration::from_millis(30);
+        assert_eq!(next_frame_instant(start, start - Duration::from_millis(10), INTERVAL), start);
+        assert_eq!(next_frame_instant(start, start - Duration::from_secs(5), INTERVAL), start);
+    }
+
+    #[test]
+    fn next_frame_is_strictly_after_now() {
+        let start = Instant::now();
+        // At the start, and exactly on later frame times, the one after is next
+        assert_eq!(next_frame_instant(start, start, INTERVAL), start + INTERVAL);
+        assert_eq!(next_frame_instant(start, start + 3 * INTERVAL, INTERVAL), start + 4 * INTERVAL);
+        // Between two frame times, the later one
+        assert_eq!(next_frame_instant(start, start + INTERVAL / 2, INTERVAL), start + INTERVAL);
+        assert_eq!(next_frame_instant(start, start + 3 * INTERVAL - Duration::from_nanos(1), INTERVAL), start + 3 * INTERVAL);
+    }
+
+    #[test]
+    fn next_frame_stays_on_the_schedule_after_a_sleep() {
+        let start = Instant::now();
+        // Eight hours asleep, plus a bit
+        let now = start + Duration::from_secs(8 * 3600) + Duration::from_millis(20);
+        let next = next_frame_instant(start, now, INTERVAL);
+        assert_eq!(next, start + Duration::from_secs(8 * 3600) + INTERVAL);
+        assert!(next > now && next - now <= INTERVAL);
+        assert_eq!((next - start).as_nanos() % INTERVAL.as_nanos(), 0);
+    }
+
+    #[test]
+    fn next_frame_with_a_zero_interval_is_now() {
+        let start = Instant::now();
+        let now = start + Duration::from_millis(7);
+        assert_eq!(next_frame_instant(start, now, Duration::ZERO), now);
+        // Still the start while that's ahead
+        let ahead = now + Duration::from_millis(7);
+        assert_eq!(next_frame_instant(ahead, now, Duration::ZERO), ahead);
+    }
+}
@@ -0,0 +1,78 @@
+// Telling whether present() waits for the display's vertical blank (vsync).
+//
+// Some platforms block in present() until the next vblank. A timer that paces the frames as well
+// then fights it: each frame is ready a little early or late against the vblank, present waits a
+// different time every frame, and the metronome's arm stutters. When presents wait, main.rs drives
+// the scenes that follow the monitor straight from them instead: the next frame is requested as
+// soon as one is shown, and the wait in present() does the pacing.
+//
+// The heuristic, over the last PACE_SAMPLES presents: present() takes a real share of the time from
+// one present returning to the next (a quarter or more, comparing medians), those returns come at an
+// even pace (the middle 80% of the gaps within a tenth of the median gap), and that pace is no faster
+// than 3/4 of the monitor's reported refresh interval. Then presents wait for vblank and the median
+// gap is the refresh interval. The last check keeps a slow copy to the screen from passing as a wait:
+// without it, frames would be drawn back to back as fast as the copy allows.
+// Checked again on every present, so a change of monitor or compositor falls back to the timer.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use log::info;
+
+/// Presents to look at, about a quarter of a second at 60 Hz.
+const PACE_SAMPLES: usize = 16;
+
+/// Recent presents and what they tell about vsync.
+pub struct PresentPacer {
+    presents:   VecDeque<(Duration, Duration)>, // how long present() took, time since the one before returned
+    last_end:   Option<Instant>,    // when the last present returned
+    vsync:      Option<Duration>,   // the refresh interval while presents wait for vblank
+}
+
+impl PresentPacer {
+    pub fn new() -> Self {
+        Self { presents: VecDeque::with_capacity(PACE_SAMPLES + 1), last_end: None, vsync: None }
+    }
+
+    /// Note a present that ran from `start` to `end`. `refresh` is the refresh interval the monitor reports.
+    pub fn record(&mut self, start: Instant, end: Instant, refresh: Duration) {
+        if let Some(last_end) = self.last_end.replace(end) {
+            self.presents.push_back((end - start, end - last_end));
+            if self.presents.len() > PACE_SAMPLES {
+                self.presents.pop_front();
+            }
+        }
+        let vsync = self.detect(refresh);
+        if vsync.is_some() != self.vsync.is_some() {
+            match vsync {
+                Some(interval) => info!("Presents wait for vblank every {:.2} ms, pacing by them", interval.as_secs_f64() * 1000.0),
+                None => info!("Presents no longer wait for vblank, pacing by the timer"),
+            }
+        }
+        self.vsync = vsync;
+    }
+
+    /// The refresh interval presents wait for, `None` unless they were seen waiting for vblank.
+    pub fn vsync_interval(&self) -> Option<Duration> {
+        self.vsync
+    }
+
+    /// The heuristic from the top of the file.
+    fn detect(&self, refresh: Duration) -> Option<Duration> {
+        let count = self.presents.len();
+        if count < PACE_SAMPLES {
+            return None;
+        }
+        let mut durations: Vec<Duration> = self.presents.iter().map(|&(duration, _)| duration).collect();
+        let mut gaps: Vec<Duration> = self.presents.iter().map(|&(_, gap)| gap).collect();
+        durations.sort_unstable();
+        gaps.sort_unstable();
+        let (duration, gap) = (durations[count / 2], gaps[count / 2]);
+        let spread = gaps[count * 9 / 10] - gaps[count / 10];
+
+        let waits = duration >= gap / 4;
+        let even = spread <= gap / 10;
+        let display_paced = gap >= refresh * 3 / 4;
+        (waits && even && display_paced).then_some(gap)
+    }
+}
@@ -0,0 +1,802 @@
+// The watch scene. Its drawing is split in the part that only changes on resize (the face)
+// and the part that changes every tick (readout, hands and hub).
+//
+// The face is drawn once into a background snapshot. Each frame the pixels under the previous
+// hands are copied back from that snapshot and the new hands drawn on top, so a frame touches
+// a few small rectangles instead of the whole window.
+// The hit-testing for dragging the minute hand with the mouse is here too, next to the geometry it mirrors.
+//
+// Measured at the default 1200x900 with the "aa" feature (release build, drawing only):
+// a full redraw took ~7.3ms, restoring the old hand areas and drawing the new hands ~0.28ms.
+// At MIN_FPS (one frame a second) that is roughly 0.7% -> 0.03% of one core.
+// The damage sent to the compositor shrinks to ~11% of the window (~120k of 1.08M pixels);
+// how much that saves on the GPU side depends on the compositor and wasn't measured.
+
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use log::{debug, info};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::chime::Chimer;
+use crate::clock::{self, TimeOfDay};
+use crate::complications;
+use crate::config::{Bezel, Complications, Numerals, SecondsDisplay, SecondsMode, Settings};
+#[cfg(not(feature = "aa"))]
+use watchrs::draw::LineCap;
+use watchrs::draw::{color_rgb, color_rgba, Canvas, Dimensions, Image, Point, Rect};
+use watchrs::font;
+use crate::hud::Hud;
+use crate::images::FaceImages;
+use crate::layout::{Layout, WatchLayout};
+use crate::scene::{Scene, SceneKind};
+use crate::theme::ThemePalette;
+use crate::time_source::{SystemClock, TimeSource};
+
+pub const MAX_FPS: u32 = 20;
+pub const MIN_FPS: u32 = 1;
+const FPS_STEP: u32 = 2;        // per +/- key press or scroll notch
+const FINE_FPS_STEP: u32 = 1;   // per up/down arrow key press
+// With power saving, wake this long after the second changes, so a timer firing
+// a little early still draws the new second instead of waking twice
+const TICK_MARGIN: Duration = Duration::from_millis(2);
+// How many presented frames back the dirty rectangles are remembered,
+// enough for double and triple buffered surfaces
+const MAX_BUFFER_AGE: usize = 3;
+
+/// Minimum distance in pixels from the minute hand that still grabs it.
+const GRAB_SLACK: f32 = 12.0;
+
+/// Longest seconds hand trail, more copies would just blur into the background
+pub const MAX_TRAIL: usize = 8;
+// Opacity of the newest trail copy over what's under it, from none (0.0) to a solid hand (1.0)
+const TRAIL_STRENGTH: f32 = 0.5;
+
+// How long the name of a face style picked with the W key stays on screen
+const STYLE_NOTICE: Duration = Duration::from_millis(1500);
+// How long the time stays up after a chime with the visual chime on
+const CHIME_NOTICE: Duration = Duration::from_secs(5);
+
+// How much lighter and darker (per channel, 0-255) the lit and shaded sides of a beveled frame are
+const BEZEL_LIGHT: i32 = 60;
+const BEZEL_DARK: i32 = 60;
+
+// Hour labels around the dial, 1 o'clock first
+const ARABIC_NUMERALS: [&str; 12] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12"];
+const ROMAN_NUMERALS: [&str; 12] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII"];
+// Hour labels stay outside this fraction of the radius inside the ticks, clear of the readout
+const LABEL_DEPTH: f32 = 0.5;
+
+/// The analog watch with its digital readout.
+pub struct WatchScene {
+    fps:          u32,
+    power_saving: bool,         // a ticking hand redraws on the second, not at fps
+    on_battery:   bool,         // the battery saver says so, the same as power saving
+    time:         TimeOfDay,
+    wall:         DateTime<Utc>, // the wall-clock time `time` was read at, for the date and moon phase
+    zone:         Option<Tz>,   // shown instead of the local time when set
+    twelve_hour:  bool,
+    twelve_hour_chosen: bool,   // toggled by the user, saved instead of following the locale
+    seconds_mode: SecondsMode,
+    seconds_display: SecondsDisplay, // a hand or a ring
+    style_picked: Option<Instant>, // when a face style was last picked with the W key, for its notice
+    chimer:       Chimer,       // the chime on the hour
+    chimed:       Option<(Instant, String)>, // when it last chimed and the time it chimed, for its notice
+    numerals:     Numerals,
+    roman_iiii:   bool,         // 4 as IIII on a Roman dial
+    bezel:        Bezel,        // flat or beveled rectangular frame
+    round:        bool,         // round bezel instead, the face and hands are clipped to it
+    complications: Complications, // date and moon phase, drawn with the face
+    images:       FaceImages,   // pictures for the dial and hands, where set
+    time_offset:  Option<f32>,  // seconds the shown time is ahead of the wall clock, None shows live time
+    dragging:     bool,         // the minute hand is being dragged with the mouse
+    trail_length: usize,        // earlier seconds hand positions to draw, 0 for no trail
+    trail:        VecDeque<f32>, // those positions in seconds, newest first
+    background:   Vec<u32>,     // the static face drawn once, empty when it needs a redraw
+    background_look: Option<(usize, usize, ThemePalette)>, // size and colors the background was drawn with
+    background_day: Option<NaiveDate>, // the day its complications show, None without any
+    dirty_history: VecDeque<Vec<Rect>>, // rects drawn over in the last frames, newest first
+}
+
+impl WatchScene {
+    /// `sound` lets the chime open the audio output.
+    pub fn new(fps: u32, zone: Option<Tz>, settings: &Settings, sound: bool) -> Self {
+        Self {
+            fps,
+            power_saving: settings.power_saving,
+            on_battery: false,
+            time: TimeOfDay::now_in(zone),
+            wall: SystemClock.wall(),
+            zone,
+            twelve_hour: settings.twelve_hour.unwrap_or_else(clock::locale_prefers_12_hour),
+            twelve_hour_chosen: settings.twelve_hour.is_some(),
+            seconds_mode: settings.seconds_mode,
+            seconds_display: settings.seconds_display,
+            style_picked: None,
+            chimer: Chimer::new(settings.chime, sound),
+            chimed: None,
+            numerals: settings.numerals,
+            roman_iiii: settings.roman_iiii,
+            bezel: settings.bezel,
+            round: settings.round_face,
+            complications: settings.complications,
+            images: FaceImages::load(&settings.face_images),
+            time_offset: None,
+            dragging: false,
+            trail_length: settings.seconds_trail.min(MAX_TRAIL),
+            trail: VecDeque::with_capacity(MAX_TRAIL + 1),
+            background: Vec::new(),
+            background_look: None,
+            background_day: None,
+            dirty_history: VecDeque::with_capacity(MAX_BUFFER_AGE + 1),
+        }
+    }
+
+    /// Raise (positive `delta`) or lower the redraw rate by `delta` FPS, within MIN_FPS..=MAX_FPS.
+    fn add_fps(&mut self, delta: i64) {
+        let fps = self.fps as i64 + delta;
+        self.fps = fps.clamp(MIN_FPS as i64, MAX_FPS as i64) as u32;
+        debug!("TPS {} to: {}", if delta > 0 { "increased" } else { "reduced" }, self.fps);
+    }
+
+    fn toggle_seconds_mode(&mut self) {
+        self.seconds_mode = self.seconds_mode.toggled();
+        info!("Seconds hand: {:?}", self.seconds_mode);
+    }
+
+    /// Switch to the face style after the current one, or to the first if the face is a mix of its
+    /// own (numerals changed with N, say), and say which it is on screen for a moment.
+    fn next_face_style(&mut self) {
+        let style = FaceStyle::of(self.numerals, self.seconds_display).map_or(FACE_STYLES[0], FaceStyle::next);
+        (self.numerals, self.seconds_display) = style.decorations();
+        self.background.clear();
+        self.trail.clear();
+        self.style_picked = Some(Instant::now());
+        info!("Face style: {}", style.name());
+    }
+}
+
+/// Built-in looks for the watch face, cycled with the W key. Each is a combination of the numerals
+/// and the seconds display, which stay settings of their own: the style is whichever one they make.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FaceStyle {
+    /// Ticks only, with a seconds hand.
+    Minimal,
+    /// Arabic numerals.
+    Numbered,
+    /// Roman numerals.
+    Roman,
+    /// Ticks only, the seconds as a ring along the dial.
+    Ring,
+}
+
+// In the order the W key goes through them
+const FACE_STYLES: [FaceStyle; 4] = [FaceStyle::Minimal, FaceStyle::Numbered, FaceStyle::Roman, FaceStyle::Ring];
+
+impl FaceStyle {
+    /// What the face is drawn with in this style.
+    pub fn decorations(self) -> (Numerals, SecondsDisplay) {
+        match self {
+            FaceStyle::Minimal => (Numerals::None, SecondsDisplay::Hand),
+            FaceStyle::Numbered => (Numerals::Arabic, SecondsDisplay::Hand),
+            FaceStyle::Roman => (Numerals::Roman, SecondsDisplay::Hand),
+            FaceStyle::Ring => (Numerals::None, SecondsDisplay::Ring),
+        }
+    }
+
+    /// The style drawn with `numerals` and `seconds_display`, `None` for a mix no style has (Roman numerals and the ring).
+    pub fn of(numerals: Numerals, seconds_display: SecondsDisplay) -> Option<Self> {
+        FACE_STYLES.into_iter().find(|style| style.decorations() == (numerals, seconds_display))
+    }
+
+    pub fn next(self) -> Self {
+        let i = FACE_STYLES.iter().position(|&style| style == self).unwrap_or(0);
+        FACE_STYLES[(i + 1) % FACE_STYLES.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FaceStyle::Minimal => "Minimal",
+            FaceStyle::Numbered => "Numbered",
+            FaceStyle::Roman => "Roman",
+            FaceStyle::Ring => "Ring",
+        }
+    }
+}
+
+impl Scene for WatchScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Watch
+    }
+
+    fn activate(&mut self, _now: Instant) {
+        // The buffers show the other scene now, the next frame has to restore the whole face.
+        // The trail is from before the switch or pause, it starts over.
+        self.dirty_history.clear();
+        self.trail.clear();
+    }
+
+    fn frame_interval(&self) -> Option<Duration> {
+        // A sweeping hand only looks smooth at a high frame rate, so it always gets MAX_FPS.
+        // Its angle comes from the wall clock at draw time (see SecondsMode::hand_seconds),
+        // the frame rate only decides how often it moves, never where it is.
+        let fps = match self.seconds_mode {
+            SecondsMode::Sweep => MAX_FPS,
+            // Paced by next_change, once a second
+            SecondsMode::Tick if self.power_saving || self.on_battery => MIN_FPS,
+            SecondsMode::Tick => self.fps.clamp(MIN_FPS, MAX_FPS),
+        };
+        Some(Duration::from_secs_f32(1.0 / fps as f32))
+    }
+
+    fn next_change(&self) -> Option<Duration> {
+        // A ticking hand and the readout only move when the whole second does, so with power saving
+        // that is the one moment worth waking for. The shown time can be offset by a fraction of a
+        // second after dragging the hands, the boundary is that of the shown time.
+        if !(self.power_saving || self.on_battery) || !matches!(self.seconds_mode, SecondsMode::Tick) {
+            return None;
+        }
+        let now = TimeOfDay::now_in(self.zone);
+        let shown = self.time_offset.map_or(now, |offset| now.offset(offset));
+        Some(Duration::from_secs_f32(1.0 - shown.seconds.fract()) + TICK_MARGIN)
+    }
+
+    #[cfg(feature = "battery-saver")]
+    fn set_on_battery(&mut self, on_battery: bool) {
+        self.on_battery = on_battery;
+    }
+
+    fn reset(&mut self, _now: Instant) {
+        // The watch has nothing to restart besides the time set by dragging the hands
+        self.time_offset = None;
+        self.dragging = false;
+        self.trail.clear();
+        info!("Showing live time");
+    }
+
+    fn update(&mut self, time: &dyn TimeSource) {
+        // Wall-clock time of day, refreshed every frame, shifted if the hands were set by hand
+        self.wall = time.wall();
+        let now = TimeOfDay::at(self.wall, self.zone);
+        self.time = self.time_offset.map_or(now, |offset| now.offset(offset));
+
+        // The chime goes by the live time, not by hands set to another one
+        if let Some((hour, minute)) = self.chimer.update(now) {
+            let hour = if self.twelve_hour { match hour % 12 { 0 => 12, h => h } } else { hour };
+            let shown = format!("{hour}:{minute:02}");
+            info!("Chiming {shown}");
+            if self.chimer.visual() {
+                self.chimed = Some((time.instant(), shown));
+            }
+        }
+
+        // Remember where the seconds hand was whenever it moves on, the newest entry is where it is now.
+        // The ring has no hand to trail.
+        if self.trail_length > 0 && self.seconds_display == SecondsDisplay::Hand {
+            let seconds = self.seconds_mode.hand_seconds(self.time.seconds);
+            if self.trail.front() != Some(&seconds) {
+                self.trail.push_front(seconds);
+                self.trail.truncate(self.trail_length + 1);
+            }
+        }
+    }
+
+    // Only the hands move, so instead of drawing the whole face every frame:
+    // 1) Draw the static face once into the background snapshot (again after a resize or face change)
+    // 2) Copy back the background where this buffer still shows old hands
+    // 3) Draw the new hands on top and remember where they went
+    // 4) Report only the old and new hand areas as changed
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>> {
+        let look = Some((layout.width, layout.height, *palette));
+        // The complications change with the day, the face is drawn again then
+        let today = self.complications.any().then(|| clock::date_at(self.wall, self.zone));
+        if self.background.is_empty() || self.background_look != look || self.background_day != today {
+            self.background = vec![0; canvas.buf.len()];
+            let mut background = Canvas::new(&mut self.background, layout.dimensions())
+                .expect("background is allocated at the layout size");
+            let labels = hour_labels(self.numerals, self.roman_iiii);
+            draw_face(&mut background, layout, palette, labels.as_ref(), self.images.dial.as_ref(), self.bezel, self.round);
+            if let Some(today) = today {
+                complications::draw(&mut background, layout, palette, self.complications, today, self.wall);
+            }
+            self.background_look = look;
+            self.background_day = today;
+            self.dirty_history.clear();
+        }
+
+        // A buffer presented n frames ago shows the hands of that frame over the unchanged face.
+        // Unknown age (0) or one older than the history: restore everything.
+        match buffer_age.checked_sub(1).and_then(|i| self.dirty_history.get(i)) {
+            Some(rects) => rects.iter().for_each(|&rect| canvas.copy_rect_from(&self.background, rect)),
+            None => canvas.buf.copy_from_slice(&self.background),
+        }
+
+        // The trail goes under the hands, its rects are restored next time like theirs
+        let trail: Vec<f32> = self.trail.iter().skip(1).copied().collect();
+        let (time, seconds_mode, twelve_hour, images) = (self.time, self.seconds_mode, self.twelve_hour, &self.images);
+        let seconds_display = self.seconds_display;
+        let draw_moving = |canvas: &mut Canvas| {
+            let mut dirty = draw_trail(canvas, layout, palette, &trail);
+            dirty.extend(draw_hands(canvas, layout, palette, time, seconds_mode, seconds_display, twelve_hour, Some(images)));
+            dirty
+        };
+        let mut dirty = if self.round {
+            canvas.with_circular_clip(layout.center, face_radius(layout), draw_moving)
+        } else {
+            draw_moving(canvas)
+        };
+        dirty.extend(hud.draw(canvas, layout, palette));
+
+        // Changed since the last presented frame: where its hands were and where the new ones are.
+        // Nothing to compare against after a rebuild, the whole window is damaged then.
+        let damage = self.dirty_history.front().map(|previous| {
+            previous.iter().chain(&dirty).copied().collect()
+        });
+        self.dirty_history.push_front(dirty);
+        self.dirty_history.truncate(MAX_BUFFER_AGE);
+        damage
+    }
+
+    fn hud_text(&self) -> String {
+        // The speed, then which zone the time is in unless it's the local one
+        let fps = self.frame_interval().map_or(0.0, |interval| 1.0 / interval.as_secs_f32());
+        match self.zone {
+            Some(zone) => format!("{} FPS {}", fps.round(), clock::zone_abbreviation(zone)),
+            None => format!("{} FPS", fps.round()),
+        }
+    }
+
+    fn notice(&self) -> Option<String> {
+        // A face style just picked is what the user is looking for, it goes over the chime
+        let style = FaceStyle::of(self.numerals, self.seconds_display)
+            .filter(|_| self.style_picked.is_some_and(|picked| picked.elapsed() < STYLE_NOTICE));
+        let chimed = self.chimed.as_ref().filter(|(at, _)| at.elapsed() < CHIME_NOTICE);
+        style.map(|style| style.name().to_string()).or_else(|| chimed.map(|(_, shown)| shown.clone()))
+    }
+
+    fn key(&mut self, key: &Key) {
+        match key {
+            // Left/right flip through the seconds modes like S, there are only two
+            Key::Character(s) if s == "s" => self.toggle_seconds_mode(),
+            Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowRight) => self.toggle_seconds_mode(),
+            Key::Named(NamedKey::ArrowUp) => self.add_fps(FINE_FPS_STEP as i64),
+            Key::Named(NamedKey::ArrowDown) => self.add_fps(-(FINE_FPS_STEP as i64)),
+            Key::Character(s) if s == "w" => self.next_face_style(),
+            Key::Character(s) if s == "n" => {
+                self.numerals = self.numerals.next();
+                self.background.clear();
+                info!("Numerals: {:?}", self.numerals);
+            }
+            Key::Character(s) if s == "d" => {
+                self.complications.date = !self.complications.date;
+                self.background.clear();
+                info!("Date {}", if self.complications.date { "on" } else { "off" });
+            }
+            Key::Character(s) if s == "m" => {
+                self.complications.moon_phase = !self.complications.moon_phase;
+                self.background.clear();
+                info!("Moon phase {}", if self.complications.moon_phase { "on" } else { "off" });
+            }
+            Key::Character(s) if s == "t" => {
+                self.twelve_hour = !self.twelve_hour;
+                self.twelve_hour_chosen = true;
+                info!("{}-hour time", if self.twelve_hour { 12 } else { 24 });
+            }
+            _ => {}
+        }
+    }
+
+    /// Raise (positive `steps`) or lower the redraw rate by FPS_STEP per step, within MIN_FPS..=MAX_FPS.
+    fn change_speed(&mut self, steps: i32) {
+        self.add_fps(steps as i64 * FPS_STEP as i64);
+    }
+
+    fn cursor_moved(&mut self, layout: &Layout, point: Point) -> bool {
+        if !self.dragging {
+            return false;
+        }
+        // Turn the hands by the shortest way to the cursor, so dragging the minute hand
+        // past 12 carries the hour hand along instead of jumping back an hour
+        let target = minutes_at(layout, point);
+        let delta_minutes = (target - self.time.minutes + 30.0).rem_euclid(60.0) - 30.0;
+        self.time_offset = Some(self.time_offset.unwrap_or(0.0) + delta_minutes * 60.0);
+        // The hand jumps while the time is set, a trail would smear across the dial
+        self.trail.clear();
+        self.time = self.time.offset(delta_minutes * 60.0);
+        true
+    }
+
+    fn mouse_button(&mut self, layout: &Layout, point: Point, pressed: bool) -> bool {
+        self.dragging = pressed && hits_minute_hand(layout, self.time, point);
+        if self.dragging {
+            info!("Setting the time by hand, R returns to live time");
+        }
+        self.dragging
+    }
+
+    #[cfg(feature = "gui")]
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.fps = settings.fps.clamp(MIN_FPS, MAX_FPS);
+        self.seconds_mode = settings.seconds_mode;
+        if let Some(twelve_hour) = settings.twelve_hour {
+            self.twelve_hour = twelve_hour;
+            self.twelve_hour_chosen = true;
+        }
+        if (settings.numerals, settings.complications) != (self.numerals, self.complications) {
+            self.numerals = settings.numerals;
+            self.complications = settings.complications;
+            self.background.clear();
+        }
+    }
+
+    fn save(&self, settings: &mut Settings) {
+        settings.fps = self.fps;
+        settings.numerals = self.numerals;
+        settings.seconds_mode = self.seconds_mode;
+        settings.seconds_display = self.seconds_display;
+        settings.complications = self.complications;
+        if self.twelve_hour_chosen {
+            settings.twelve_hour = Some(self.twelve_hour);
+        }
+    }
+}
+
+/// Everything that doesn't move: background, frame, dial, ticks and numerals.
+/// `labels` go around the dial from 1 o'clock to 12, None leaves only the ticks.
+/// The frame is drawn in the `bezel` style. A `round` face has a circular bezel instead of the
+/// frame, and the dial is clipped to it. A `dial_image` is drawn instead of the dial, scaled to it.
+pub fn draw_face(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, labels: Option<&[&str; 12]>, dial_image: Option<&Image>, bezel: Bezel, round: bool) {
+    let draw_dial = |canvas: &mut Canvas| match dial_image {
+        Some(image) => {
+            let scale = (2 * layout.watch.dial_radius) as f32 / image.width.max(image.height) as f32;
+            canvas.blit_rotated(image, layout.center, -FRAC_PI_2, scale);
+        }
+        None => draw_dial(canvas, layout, palette, labels),
+    };
+    palette.fill_background(canvas);
+    if round {
+        // The bezel sits where the frame would touch the dial, as far out as the frame's padding
+        let bezel_radius = layout.watch.dial_radius + layout.frame_padding;
+        canvas.draw_circle(layout.center, bezel_radius as isize, layout.thick as isize, palette.foreground);
+        canvas.with_circular_clip(layout.center, face_radius(layout), draw_dial);
+    } else {
+        let (padding, thick, foreground) = (layout.frame_padding, layout.thick, palette.foreground);
+        match bezel {
+            Bezel::Flat => canvas.draw_frame(padding, thick, foreground),
+            Bezel::Raised => canvas.draw_bezel(padding, thick, foreground, BEZEL_LIGHT, BEZEL_DARK),
+            Bezel::Inset => canvas.draw_bezel(padding, thick, foreground, -BEZEL_DARK, -BEZEL_LIGHT),
+        }
+        draw_dial(canvas);
+    }
+}
+
+/// Radius of a round face: the dial with its outline, everything past it is cut off.
+fn face_radius(layout: &Layout) -> usize {
+    layout.watch.dial_radius + layout.watch.dial_thick
+}
+
+/// Dial outline, ticks and numerals around the layout's center, without the background or frame.
+pub fn draw_dial(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, labels: Option<&[&str; 12]>) {
+    let watch = layout.watch;
+    let center = layout.center;
+    let foreground = palette.foreground;
+
+    // Dial outline
+    canvas.draw_circle(center, watch.dial_radius as isize, watch.dial_thick as isize, foreground);
+
+    // Tick marks: 60 minute ticks, every 5th one is a longer and thicker hour tick
+    for i in 0..60 {
+        let angle = i as f32 * (TAU / 60.0);
+        let (length, thickness) = if i % 5 == 0 { watch.hour_tick } else { watch.minute_tick };
+        let inner = watch.dial_radius.saturating_sub(length);
+        canvas.draw_tick(center, inner, watch.dial_radius, angle, thickness, foreground);
+    }
+
+    // Hour labels just inside the hour ticks. Each label is centered as a whole
+    // on its position, so "10", "VIII" and "XII" don't lean off to one side.
+    if let Some(labels) = labels {
+        let (scale, radii) = fit_labels(&watch, labels);
+        let half_height = (font::text_height(scale) / 2) as isize;
+        for (i, label) in labels.iter().enumerate() {
+            let label_center = center.polar(label_angle(i), radii[i]);
+            let origin = Point::new(
+                label_center.x - (font::text_width(label, scale) / 2) as isize,
+                label_center.y - half_height,
+            );
+            canvas.draw_text(origin, label, scale, foreground);
+        }
+    }
+}
+
+/// The labels `numerals` puts around the dial, None for ticks only.
+/// `roman_iiii` writes 4 as IIII like most Roman watch dials do.
+pub fn hour_labels(numerals: Numerals, roman_iiii: bool) -> Option<[&'static str; 12]> {
+    match numerals {
+        Numerals::Arabic => Some(ARABIC_NUMERALS),
+        Numerals::Roman => {
+            let mut labels = ROMAN_NUMERALS;
+            if roman_iiii {
+                labels[3] = "IIII";
+            }
+            Some(labels)
+        }
+        Numerals::None => None,
+    }
+}
+
+/// Angle of the `i`th label, 0 is 1 o'clock.
+fn label_angle(i: usize) -> f32 {
+    -FRAC_PI_2 + (i + 1) as f32 * (TAU / 12.0)
+}
+
+/// Text scale and the radius of each hour label's center: the largest scale up to the layout's
+/// at which no label touches its neighbours or reaches into the middle of the dial, with every
+/// label as close to its hour tick as its size allows.
+fn fit_labels(watch: &WatchLayout, labels: &[&str; 12]) -> (usize, [f32; 12]) {
+    let inner = watch.dial_radius.saturating_sub(watch.hour_tick.0) as f32;
+    let mut fit = (1, [0.0; 12]);
+    for scale in (1..=watch.numeral_scale).rev() {
+        let gap = scale as f32;
+        let height = font::text_height(scale) as f32;
+        let widths = labels.map(|label| font::text_width(label, scale) as f32);
+
+        // How far each label's box reaches from its center towards the tick, a wide label
+        // at 3 or 9 o'clock reaches further than at 12 or 6
+        let reach: [f32; 12] = std::array::from_fn(|i| {
+            let angle = label_angle(i);
+            angle.cos().abs() * widths[i] / 2.0 + angle.sin().abs() * height / 2.0
+        });
+        // Clear of the round end of the hour tick by a gap
+        let radii = reach.map(|reach| (inner - watch.hour_tick.1 as f32 - reach - gap).max(0.0));
+        fit = (scale, radii);
+
+        // Two neighbouring boxes overlap if they're closer than their sizes on both axes
+        let overlaps = (0..12).any(|i| {
+            let j = (i + 1) % 12;
+            let dx = radii[i] * label_angle(i).cos() - radii[j] * label_angle(j).cos();
+            let dy = radii[i] * label_angle(i).sin() - radii[j] * label_angle(j).sin();
+            dx.abs() < (widths[i] + widths[j]) / 2.0 + gap && dy.abs() < height + gap
+        });
+        let too_deep = (0..12).any(|i| radii[i] - reach[i] < inner * LABEL_DEPTH);
+        if !overlaps && !too_deep {
+            break;
+        }
+    }
+    fit
+}
+
+/// Everything that moves: digital readout, hands and hub, with the seconds as a hand or a ring
+/// (`seconds_display`). Hands with a picture in `images` are drawn with it, scaled to their length.
+///
+/// Returns the rectangles that were drawn over, so the next frame knows what to restore.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_hands(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, time: TimeOfDay, seconds_mode: SecondsMode, seconds_display: SecondsDisplay, twelve_hour: bool, images: Option<&FaceImages>) -> Vec<Rect> {
+    let watch = layout.watch;
+    let foreground = palette.foreground;
+    let center = layout.center;
+    let (width, height) = (layout.width, layout.height);
+    let TimeOfDay { hours, minutes, seconds } = time;
+    let hours = hours % 12.0; // the dial only shows 12 hours
+
+    // The seconds hand ticks or sweeps depending on the mode, minutes and hours move continuously
+    let hand_seconds = seconds_mode.hand_seconds(seconds);
+    let up = -FRAC_PI_2;            // 12 o'clock is -90°
+    let seconds_angle = up + hand_seconds * (TAU / 60.0);
+    let minute_angle = up + minutes * (TAU / 60.0);
+    let hour_angle = up + hours * (TAU / 12.0);
+
+    // Digital readout below the center, under the hands
+    let text = time.format(twelve_hour);
+    let text_width = font::text_width(&text, watch.readout_scale);
+    let text_origin = Point::new(center.x - (text_width / 2) as isize, watch.readout_y);
+    canvas.draw_text(text_origin, &text, watch.readout_scale, foreground);
+    let text_end = Point::new(
+        text_origin.x + text_width as isize,
+        text_origin.y + font::text_height(watch.readout_scale) as isize,
+    );
+    let mut dirty = vec![Rect::around(&[text_origin, text_end], 0, width, height)];
+
+    // The ring goes along the dial, under the hands
+    if seconds_display == SecondsDisplay::Ring {
+        dirty.extend(draw_seconds_ring(canvas, layout, palette, hand_seconds));
+    }
+
+    // Draw the hands, hour hand first so the thinner ones end up on top.
+    // Each hand dirties the box from the pivot to its tip, grown by its thickness
+    // (plus one pixel for the anti-aliased edge).
+    let hands = [
+        (hour_angle, watch.hour_hand, palette.hour_hand, images.and_then(|images| images.hour_hand.as_ref())),
+        (minute_angle, watch.minute_hand, palette.minute_hand, images.and_then(|images| images.minute_hand.as_ref())),
+        (seconds_angle, watch.seconds_hand, palette.seconds_hand, images.and_then(|images| images.seconds_hand.as_ref())),
+    ];
+    let hand_count = match seconds_display {
+        SecondsDisplay::Hand => hands.len(),
+        SecondsDisplay::Ring => hands.len() - 1,
+    };
+    for (angle, (length, thick), color, image) in hands.into_iter().take(hand_count) {
+        if let Some(image) = image {
+            // The picture's height from its base is the hand's length
+            dirty.push(canvas.blit_rotated(image, center, angle, length / image.pivot.1));
+            continue;
+        }
+        let tip = center.polar(angle, length);
+        draw_hand(canvas, center, tip, thick, color);
+        dirty.push(Rect::around(&[center, tip], thick + 1, width, height));
+    }
+
+    // Hub cap over the pivot, hides where the hands overlap
+    canvas.draw_filled_circle(center, watch.hub_radius, palette.hub);
+    dirty.push(Rect::around(&[center], watch.hub_radius + 1, width, height));
+
+    dirty
+}
+
+/// Copies of the seconds hand at the `trail` positions (in seconds, newest first), each one fainter
+/// than the one before. They're blended over whatever is drawn under them (a gradient, ticks,
+/// numerals), pixel by pixel.
+///
+/// Returns the rectangles that were drawn over, like `draw_hands`.
+fn draw_trail(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, trail: &[f32]) -> Vec<Rect> {
+    let center = layout.center;
+    let (length, thick) = layout.watch.seconds_hand;
+    let [_, red, green, blue] = palette.seconds_hand.to_be_bytes();
+    let mut dirty = Vec::with_capacity(trail.len());
+    let mut mask = Vec::new();
+    for (i, &seconds) in trail.iter().enumerate() {
+        let strength = TRAIL_STRENGTH * (1.0 - i as f32 / trail.len() as f32);
+        let tip = center.polar(-FRAC_PI_2 + seconds * (TAU / 60.0), length);
+        let rect = Rect::around(&[center, tip], thick + 1, layout.width, layout.height);
+
+        // The hand drawn white on black by itself first, in the box around it: how much of each
+        // pixel it covers (less on anti-aliased edges), for the alpha to blend its color in with
+        let size = Dimensions { width: rect.width(), height: rect.height() };
+        let origin = Point::new(rect.x0 as isize, rect.y0 as isize);
+        mask.clear();
+        mask.resize(size.width * size.height, 0);
+        {
+            let mut hand = Canvas::new(&mut mask, size).expect("mask matches the box");
+            let at = |p: Point| Point::new(p.x - origin.x, p.y - origin.y);
+            draw_hand(&mut hand, at(center), at(tip), thick, color_rgb(255, 255, 255));
+        }
+        for (j, &covered) in mask.iter().enumerate() {
+            let alpha = (strength * (covered & 0xFF) as f32).round() as u8;
+            if alpha > 0 {
+                let (x, y) = (origin.x + (j % size.width) as isize, origin.y + (j / size.width) as isize);
+                canvas.blend_pixel(x, y, color_rgba(red, green, blue, alpha));
+            }
+        }
+        dirty.push(rect);
+    }
+    dirty
+}
+
+/// The seconds as an arc along the dial, clockwise from 12 o'clock to `seconds` (0 to 60).
+/// Nothing at all on the full minute, so the ring starts empty.
+///
+/// Returns the box around the arc: its ends and the quarters of the dial it passes, grown by its thickness.
+fn draw_seconds_ring(canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, seconds: f32) -> Vec<Rect> {
+    if seconds <= 0.0 {
+        return Vec::new();
+    }
+    let (center, radius) = (layout.center, layout.watch.dial_radius);
+    let thick = layout.watch.hour_tick.1;
+    let up = -FRAC_PI_2;
+    let end = up + seconds * (TAU / 60.0);
+    canvas.draw_arc(center, radius, up, end, thick, palette.seconds_hand);
+
+    let quarters = (1..4).filter(|&quarter| (quarter * 15) as f32 <= seconds).map(|quarter| up + quarter as f32 * FRAC_PI_2);
+    let points: Vec<Point> = [up, end].into_iter().chain(quarters).map(|angle| center.polar(angle, radius as f32)).collect();
+    vec![Rect::around(&points, thick / 2 + 1, layout.width, layout.height)]
+}
+
+/// One clock hand from the pivot `from` to its tip `to`, with flat ends.
+/// Anti-aliased with the "aa" feature (whose lines end flat anyway), a filled quad otherwise.
+pub fn draw_hand(canvas: &mut Canvas, from: Point, to: Point, thick: usize, color: u32) {
+    #[cfg(feature = "aa")]
+    canvas.draw_line_aa(from, to, thick, color);
+    #[cfg(not(feature = "aa"))]
+    canvas.draw_line_capped(from, to, thick, LineCap::Flat, color);
+}
+
+/// Whether `point` is on (or close enough to grab) the minute hand showing `time`.
+pub fn hits_minute_hand(layout: &Layout, time: TimeOfDay, point: Point) -> bool {
+    let watch = layout.watch;
+    let center = layout.center;
+    let (length, thick) = watch.minute_hand;
+    let tip = center.polar(-FRAC_PI_2 + time.minutes * (TAU / 60.0), length);
+
+    // Distance from the point to the hand segment: project onto it and clamp to its ends
+    let (dx, dy) = ((tip.x - center.x) as f32, (tip.y - center.y) as f32);
+    let (px, py) = ((point.x - center.x) as f32, (point.y - center.y) as f32);
+    let t = ((px * dx + py * dy) / (dx * dx + dy * dy).max(1.0)).clamp(0.0, 1.0);
+    let distance = (px - t * dx).hypot(py - t * dy);
+
+    // A thin hand is hard to hit exactly, grant a few pixels of slack
+    distance <= (thick as f32).max(GRAB_SLACK)
+}
+
+/// Minutes (0..60) the minute hand would show pointing from the center towards `point`.
+pub fn minutes_at(layout: &Layout, point: Point) -> f32 {
+    let center = layout.center;
+    let angle = ((point.y - center.y) as f32).atan2((point.x - center.x) as f32);
+    (angle + FRAC_PI_2).rem_euclid(TAU) * (60.0 / TAU)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::render;
+    use crate::time_source::FakeClock;
+
+    const SIZE: usize = 400;
+
+    /// The default watch at `hours`:`minutes`:`seconds` UTC, drawn like --render does, with the
+    /// layout and colors it was drawn with.
+    fn render_at(hours: u32, minutes: u32, seconds: u32) -> (Vec<u32>, Layout, ThemePalette) {
+        let settings = Settings::default();
+        let mut scene = WatchScene::new(MIN_FPS, Some(chrono_tz::UTC), &settings, false);
+        let layout = Layout::new(SIZE, SIZE, settings.hand_lengths, 1.0, settings.dial_position);
+        let palette = settings.palette(SceneKind::Watch);
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2024, 3, 10, hours, minutes, seconds).unwrap());
+        let pixels = render::render_frame(&layout, &mut scene, &palette, false, &clock);
+        (pixels, layout, palette)
+    }
+
+    /// Just inside the tip of a seconds hand showing `seconds`, where only that hand draws.
+    fn seconds_tip(layout: &Layout, seconds: u32) -> Point {
+        let (length, _) = layout.watch.seconds_hand;
+        layout.center.polar(-FRAC_PI_2 + seconds as f32 * (TAU / 60.0), length - 2.0)
+    }
+
+    #[test]
+    fn seconds_hand_tip_lands_where_the_time_says() {
+        // The hour and minute hands point between 7 and 8, away from all four quarters
+        for seconds in [0, 15, 30, 45] {
+            let (mut pixels, layout, palette) = render_at(7, 38, seconds);
+            let canvas = Canvas::new(&mut pixels, layout.dimensions()).unwrap();
+            for quarter in [0, 15, 30, 45] {
+                let tip = seconds_tip(&layout, quarter);
+                let is_hand = canvas.get_pixel(tip.x, tip.y) == Some(palette.seconds_hand);
+                assert_eq!(is_hand, quarter == seconds, "{quarter} s position at {seconds} s ({tip:?})");
+            }
+        }
+    }
+
+    #[test]
+    fn seconds_hand_tips_are_a_quarter_turn_apart() {
+        let (_, layout, _) = render_at(7, 38, 0);
+        let (top, right, bottom, left) = (seconds_tip(&layout, 0), seconds_tip(&layout, 15), seconds_tip(&layout, 30), seconds_tip(&layout, 45));
+        let center = layout.center;
+        assert_eq!((top.x, right.y, bottom.x, left.y), (center.x, center.y, center.x, center.y));
+        assert!(top.y < center.y && right.x > center.x && bottom.y > center.y && left.x < center.x);
+        assert_eq!(center.y - top.y, right.x - center.x);
+    }
+
+    #[test]
+    fn seconds_trail_blends_over_what_is_under_it() {
+        // Two colors under the trail copy of a hand at 15 s, which points right from the center
+        let settings = Settings::default();
+        let layout = Layout::new(SIZE, SIZE, settings.hand_lengths, 1.0, settings.dial_position);
+        let palette = settings.palette(SceneKind::Watch);
+        let (near, far) = (color_rgb(200, 40, 40), color_rgb(40, 40, 200));
+        let (length, _) = layout.watch.seconds_hand;
+        let split = layout.center.x + length as isize / 2;
+        let mut pixels: Vec<u32> = (0..SIZE * SIZE).map(|i| if ((i % SIZE) as isize) < split { near } else { far }).collect();
+        let mut canvas = Canvas::new(&mut pixels, layout.dimensions()).unwrap();
+        draw_trail(&mut canvas, &layout, &palette, &[15.0]);
+
+        let alpha = (TRAIL_STRENGTH * 255.0).round() / 255.0;
+        let y = layout.center.y;
+        for (x, under) in [(split - length as isize / 4, near), (split + length as isize / 4, far)] {
+            let expected = watchrs::draw::blend(under, palette.seconds_hand, alpha);
+            assert_eq!(canvas.get_pixel(x, y), Some(expected), "({x}, {y}) over {under:06x}");
+        }
+        // Off the hand nothing changes
+        assert_eq!(canvas.get_pixel(split - length as isize / 4, y - 20), Some(near));
+    }
+}
@@ -0,0 +1,516 @@
+// One window and everything that goes with showing the scenes in it: the softbuffer surface, the
+// layout, the frame pacing, the pause, the overlays and the close fade.
+//
+// Usually there's one. With --per-monitor main.rs opens one on every monitor, each fullscreen on
+// its own and drawn and paced independently (the monitors may refresh at different rates). Each
+// window has its own copy of the scenes: a scene remembers what it drew last (the watch repaints
+// only what changed since) and that differs from one surface to the next. Only the first window's
+// scenes make sound, one click per beat is enough.
+//
+// What belongs to the app rather than a window (the settings, the frame timer, quitting) stays in
+// main.rs, which hands the settings to the methods here that read them.
+
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+use watchrs::draw;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Fullscreen, Window, WindowAttributes};
+
+use crate::config::Settings;
+use crate::error::AppError;
+#[cfg(feature = "gui")]
+use crate::panel;
+use crate::time_source::SystemClock;
+use crate::{controls, help, hud, layout, scene, theme, timing, vsync};
+
+// Redraw rate for scenes that follow the display, when the monitor doesn't report its refresh rate
+const FALLBACK_REFRESH_MILLIHERTZ: u32 = 60_000;
+
+// How long the colors take to fade into the background on close, with fade_on_close set
+const CLOSE_FADE: Duration = Duration::from_millis(200);
+
+pub struct WindowState {
+    pub window: Rc<Window>,
+    surface:    softbuffer::Surface<Rc<Window>, Rc<Window>>,
+    pending_size: Option<(NonZeroU32, NonZeroU32)>, // window size the surface still has to be resized to
+    fullscreen: bool,
+    pub palette: theme::ThemePalette,   // colors of the current theme for the active scene, with the configured ones applied
+    pub theme_transition: Option<theme::ThemeTransition>, // fading into `palette` after a theme change
+    pub scenes: Vec<Box<dyn scene::Scene>>, // every scene built in, each keeps its state while the other one is shown
+    pub active: usize,                  // index of the scene the window shows
+    paused:     bool,                   // the scene is frozen: no updates, no clicks, no timed redraws
+    pub show_help: bool,                // the key help is over the scene (F1 or ?)
+    #[cfg(feature = "gui")]
+    pub show_panel: bool,               // the settings panel is over the scene (G)
+    #[cfg(feature = "gui")]
+    pub slider: Option<panel::Field>,   // the panel slider held down with the mouse
+    closing:    Option<Instant>,        // when the close fade started, the window closes once it's done
+    pub next_frame: Instant,
+    refresh_interval: Duration, // one frame of the monitor the window is on, for scenes without their own interval
+    present_pacer: vsync::PresentPacer, // whether presents wait for vblank, see vsync.rs
+    pub cursor: draw::Point,            // last cursor position, clamped to the window
+    pub cursor_moved_at: Option<Instant>,   // last cursor move over the window, the on-screen buttons show for a while after
+    pub scroll: f64,                    // scrolled distance in steps not applied yet (trackpads send fractions)
+    pub layout: layout::Layout,
+    scale_factor: f64,          // the window's physical pixels per logical one, the layout's thinnest line
+    pub failed_frames: u32,     // frames in a row that failed to show, see MAX_FAILED_FRAMES in main.rs
+}
+
+impl WindowState {
+    /// Open a window with `attrs` showing `scenes`, with its surface sized and laid out for the window's actual size.
+    pub fn open(
+        event_loop: &ActiveEventLoop,
+        attrs: WindowAttributes,
+        scenes: Vec<Box<dyn scene::Scene>>,
+        settings: &Settings,
+    ) -> Result<Self, AppError> {
+        let fullscreen = attrs.fullscreen.is_some();
+        // With an Rc we 'own' the window and hand owned handles to softbuffer
+        let window = Rc::new(event_loop.create_window(attrs).map_err(AppError::CreateWindow)?);
+        let context = softbuffer::Context::new(window.clone()).map_err(AppError::Context)?;
+        let surface = softbuffer::Surface::new(&context, window.clone()).map_err(AppError::Surface)?;
+
+        let scale_factor = window.scale_factor();
+        let mut state = Self {
+            window,
+            surface,
+            pending_size: None,
+            fullscreen,
+            palette: settings.palette(scenes[0].kind()),
+            theme_transition: None,
+            scenes,
+            active: 0,
+            paused: false,
+            show_help: false,
+            #[cfg(feature = "gui")]
+            show_panel: false,
+            #[cfg(feature = "gui")]
+            slider: None,
+            closing: None,
+            next_frame: Instant::now(),
+            refresh_interval: Duration::from_secs_f64(1000.0 / FALLBACK_REFRESH_MILLIHERTZ as f64),
+            present_pacer: vsync::PresentPacer::new(),
+            cursor: draw::Point::new(0, 0),
+            cursor_moved_at: None,
+            scroll: 0.0,
+            layout: layout::Layout::new(crate::WIDTH, crate::HEIGHT, settings.hand_lengths, scale_factor, settings.dial_position),
+            scale_factor,
+            failed_frames: 0,
+        };
+
+        // resize the surface to the actual inner_size (PHYSICAL)
+        // A zero size (e.g. a window that starts minimized) is skipped, the Resized event fixes it later
+        let window_size = state.window.inner_size();
+        if let Some((width, height)) = clamp_surface_size(window_size.width, window_size.height) {
+            state.surface.resize(width, height).map_err(|e| AppError::Resize(width.get(), height.get(), e))?;
+            state.relayout(width.get() as usize, height.get() as usize, settings);
+        }
+        state.update_refresh_interval();
+        Ok(state)
+    }
+
+    /// The scene the window shows.
+    pub fn scene(&mut self) -> &mut dyn scene::Scene {
+        self.scenes[self.active].as_mut()
+    }
+
+    /// Lay the scenes out for a `width` x `height` window at the current scale factor.
+    pub fn relayout(&mut self, width: usize, height: usize, settings: &Settings) {
+        self.layout = layout::Layout::new(width, height, settings.hand_lengths, self.scale_factor, settings.dial_position);
+    }
+
+    /// The window moved to a monitor with another DPI (or the setting changed).
+    pub fn rescale(&mut self, scale_factor: f64, settings: &Settings) {
+        self.scale_factor = scale_factor;
+        self.update_refresh_interval();
+        self.relayout(self.layout.width, self.layout.height, settings);
+        self.window.request_redraw();
+    }
+
+    /// The window got a new size: the layout follows now, the surface when the next frame is drawn.
+    pub fn resize(&mut self, width: NonZeroU32, height: NonZeroU32, settings: &Settings) {
+        self.pending_size = Some((width, height));
+        self.relayout(width.get() as usize, height.get() as usize, settings);
+        self.window.request_redraw();
+    }
+
+    /// Whether the on-screen buttons show: the cursor moved over the window within CONTROLS_TIMEOUT.
+    pub fn controls_shown(&self) -> bool {
+        self.controls_hide_at().is_some_and(|at| Instant::now() < at)
+    }
+
+    /// When the on-screen buttons hide if the cursor doesn't move again, `None` while hidden.
+    fn controls_hide_at(&self) -> Option<Instant> {
+        self.cursor_moved_at.map(|at| at + controls::CONTROLS_TIMEOUT)
+    }
+
+    /// The refresh interval when presents pace the active scene: they wait for vblank (see vsync.rs)
+    /// and the scene follows the monitor rather than its own frame interval. `None` while the timer paces it.
+    fn vsync_paced(&self) -> Option<Duration> {
+        let follows_monitor = !self.paused && self.closing.is_none() && self.scenes[self.active].frame_interval().is_none();
+        self.present_pacer.vsync_interval().filter(|_| follows_monitor)
+    }
+
+    /// Do what the on-screen button for `control` does, the same as its key.
+    pub fn press_control(&mut self, control: controls::Control) {
+        match control {
+            controls::Control::Slower => self.scene().change_speed(-1),
+            controls::Control::Faster => self.scene().change_speed(1),
+            controls::Control::Pause => self.toggle_pause(),
+        }
+        self.window.request_redraw();
+    }
+
+    /// Draw and show the next frame of the active scene, returns whether one was shown.
+    /// A failed resize stays pending, so the next frame tries it again.
+    /// `frame_timer` measures the frame, with --stats.
+    pub fn redraw(&mut self, settings: &mut Settings, frame_timer: Option<&mut timing::FrameTimer>) -> Result<bool, AppError> {
+        let controls_shown = self.controls_shown();
+        let window_size = self.window.inner_size();
+
+        // Nothing to draw into while the window has no area (minimized), pause until it's back
+        if clamp_surface_size(window_size.width, window_size.height).is_none() {
+            return Ok(false);
+        }
+
+        // One check per frame, nothing is measured without --stats
+        let frame_start = frame_timer.is_some().then(Instant::now);
+
+        // Acquire the frame, all sizes come from the layout cached at the last resize
+        let layout = self.layout;
+        if let Some((width, height)) = self.pending_size {
+            self.surface.resize(width, height).map_err(|e| AppError::Resize(width.get(), height.get(), e))?;
+            self.pending_size = None;
+        }
+        let mut canvas_buffer = self.surface.buffer_mut().map_err(AppError::Buffer)?;
+        // How many frames ago this buffer was last presented, 0 if its content is unknown.
+        // A transparent window's last frame was keyed out and can't be drawn over, it's always redrawn whole.
+        let buffer_age = if settings.transparent { 0 } else { canvas_buffer.age() as usize };
+
+        // The surface may still be sized for the previous window size mid-resize,
+        // skip this frame and try again once the Resized event has caught up.
+        let mut canvas = match draw::Canvas::new(&mut canvas_buffer, layout.dimensions()) {
+            Ok(canvas) => canvas,
+            Err(e) => {
+                debug!("Skipping frame: surface has {} pixels, window needs {}", e.actual, e.expected);
+                self.window.request_redraw();
+                return Ok(false);
+            }
+        };
+
+        let scene_count = self.scenes.len();
+        let scene = &mut self.scenes[self.active];
+        if !self.paused {
+            scene.update(&SystemClock);
+        }
+        let hud = hud::Hud {
+            text: settings.show_hud.then(|| scene.hud_text()),
+            notice: scene.notice(),
+            controls: controls_shown.then(|| controls::hit(&layout, self.cursor)),
+            // With the speed and options the scene has now, changed by keys too while the panel is open
+            #[cfg(feature = "gui")]
+            panel: self.show_panel.then(|| {
+                scene.save(settings);
+                panel::rows(settings, scene.kind())
+            }),
+            help: self.show_help.then(|| help::bindings(scene.kind(), scene_count)),
+        };
+        // Part of the way into a new theme after a change, and into the background while closing
+        let palette = match &self.theme_transition {
+            Some(transition) => transition.palette_at(Instant::now()),
+            None => self.palette,
+        };
+        let palette = match self.closing {
+            Some(start) => palette.faded(start.elapsed().as_secs_f32() / CLOSE_FADE.as_secs_f32()),
+            None => palette,
+        };
+        let damage = scene.draw(&mut canvas, &layout, &palette, buffer_age, &hud);
+        if settings.transparent {
+            canvas.key_out(palette.background);
+        }
+
+        self.window.pre_present_notify();
+        let present_start = Instant::now();
+        match damage.map(|rects| rects.into_iter().filter_map(damage_rect).collect::<Vec<_>>()) {
+            Some(damage) => canvas_buffer.present_with_damage(&damage),
+            None => canvas_buffer.present(),
+        }
+        .map_err(AppError::Present)?;
+        self.present_pacer.record(present_start, Instant::now(), self.refresh_interval);
+
+        // Presents wait for vblank: ask for the next frame right away and let present() pace it.
+        // The timer only steps in if no frame follows for a couple of refresh intervals.
+        if let Some(interval) = self.vsync_paced() {
+            self.next_frame = Instant::now() + interval * 2;
+            self.window.request_redraw();
+        }
+
+        if let (Some(timer), Some(start)) = (frame_timer, frame_start) {
+            timer.record(start.elapsed());
+        }
+        Ok(true)
+    }
+
+    /// Ask for the frames that are due and return when this window needs waking next,
+    /// `None` if only events (the OS asking for a redraw, a key) change what it shows.
+    pub fn schedule(&mut self, now: Instant) -> Option<Instant> {
+        // Fading out: redraw as fast as the display shows frames until the fade is over
+        if self.closing.is_some() {
+            self.window.request_redraw();
+            return Some(now + self.refresh_interval);
+        }
+
+        // The on-screen buttons hide a while after the cursor stops. That frame isn't on the
+        // scene's schedule (and a paused scene has none), so it gets its own wake-up.
+        if let Some(at) = self.controls_hide_at()
+            && now >= at
+        {
+            self.cursor_moved_at = None;
+            self.window.request_redraw();
+        }
+
+        // Nothing changes while paused, only the OS asks for redraws (uncovering, resizing)
+        if self.paused {
+            return earliest([self.controls_hide_at(), self.fade_frame(now)]);
+        }
+
+        // A click due before the next frame (a metronome redrawn less often than it clicks) is played
+        // on time by updating the scene for it now, the frame shows where that got to later
+        let scene = &mut self.scenes[self.active];
+        if scene.next_click().is_some_and(|at| now >= at) {
+            scene.update(&SystemClock);
+        }
+        let next_click = scene.next_click();
+
+        // A theme fading in takes a frame per refresh of the monitor, whatever the scene's pace
+        if let Some(at) = self.fade_frame(now) {
+            return earliest([Some(at), next_click]);
+        }
+
+        // Scenes without a frame interval (the metronome) redraw once per frame of the monitor,
+        // drawing faster than the display shows would only burn CPU. When presents wait for vblank
+        // they pace those scenes themselves (see redraw), and next_frame stays ahead of now.
+        let scene = &self.scenes[self.active];
+        let frame_duration = scene.frame_interval().unwrap_or(self.refresh_interval);
+
+        // Are we at/after the scheduled time?
+        if now >= self.next_frame {
+            // 1) Request exactly one redraw for this tick
+            self.window.request_redraw();
+
+            // A scene that knows when it changes next (a ticking watch saving power) is woken
+            // right then, the others keep a steady pace of frame_duration
+            if let Some(wait) = scene.next_change() {
+                self.next_frame = now + wait;
+            } else {
+                self.next_frame = timing::next_frame_instant(self.next_frame, now, frame_duration);
+            }
+        }
+        earliest([Some(self.next_frame), self.controls_hide_at(), next_click])
+    }
+
+    /// Ask for the next frame of a theme fade and return when the one after is due, `None` without a fade.
+    /// The frame after the fade is over shows the new colors as they are.
+    fn fade_frame(&mut self, now: Instant) -> Option<Instant> {
+        let transition = self.theme_transition.as_ref()?;
+        self.window.request_redraw();
+        if transition.done(now) {
+            self.theme_transition = None;
+            return None;
+        }
+        Some(now + self.refresh_interval)
+    }
+
+    /// Start fading out, returns false if the window was already fading (it closes right away then).
+    pub fn start_closing(&mut self) -> bool {
+        self.closing.replace(Instant::now()).is_none()
+    }
+
+    /// Whether the close fade is over and the window can go.
+    pub fn faded_out(&self) -> bool {
+        self.closing.is_some_and(|start| start.elapsed() >= CLOSE_FADE)
+    }
+
+    /// Hand the speeds and options of the window's scenes to `settings`, to be saved.
+    pub fn save(&self, settings: &mut Settings) {
+        for scene in &self.scenes {
+            scene.save(settings);
+        }
+    }
+
+    /// Switch between a borderless fullscreen window and the normal one.
+    /// The surface follows through the Resized event the window sends afterwards.
+    pub fn toggle_fullscreen(&mut self) {
+        self.fullscreen = !self.fullscreen;
+        self.window.set_fullscreen(self.fullscreen.then_some(Fullscreen::Borderless(None)));
+        info!("Fullscreen {}", if self.fullscreen { "on" } else { "off" });
+    }
+
+    /// Look up the refresh rate of the monitor the window is on, again whenever it may have changed monitors.
+    /// Some platforms (and virtual displays) don't report one, those get FALLBACK_REFRESH_MILLIHERTZ.
+    pub fn update_refresh_interval(&mut self) {
+        let millihertz = self
+            .window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .filter(|&millihertz| millihertz > 0)
+            .unwrap_or(FALLBACK_REFRESH_MILLIHERTZ);
+        let interval = Duration::from_secs_f64(1000.0 / millihertz as f64);
+        if interval != self.refresh_interval {
+            self.refresh_interval = interval;
+            debug!("Refresh rate: {:.2} Hz", millihertz as f64 / 1000.0);
+        }
+    }
+
+    /// Tell the scenes the computer went on battery or back on AC power.
+    /// The watch's frame rate changes with it, the next frame is drawn on the new schedule.
+    #[cfg(feature = "battery-saver")]
+    pub fn set_on_battery(&mut self, on_battery: bool) {
+        for scene in &mut self.scenes {
+            scene.set_on_battery(on_battery);
+        }
+        self.next_frame = Instant::now();
+    }
+
+    /// Fade from the colors shown now (part of the way into another theme, mid-fade) into the
+    /// settings' colors for the active scene. Nothing happens if those are the colors already.
+    pub fn fade_to(&mut self, settings: &Settings) {
+        let palette = settings.palette(self.scenes[self.active].kind());
+        if palette != self.palette {
+            let shown = self.theme_transition.as_ref().map_or(self.palette, |transition| transition.palette_at(Instant::now()));
+            self.theme_transition = Some(theme::ThemeTransition::new(shown, palette));
+            self.palette = palette;
+        }
+    }
+
+    /// Open or close the settings panel. Opening it closes the key help, which would cover it.
+    #[cfg(feature = "gui")]
+    pub fn toggle_panel(&mut self) {
+        self.show_panel = !self.show_panel;
+        self.show_help &= !self.show_panel;
+        self.slider = None;
+    }
+
+    /// Let the settings panel take a press at the cursor: change the setting there.
+    /// Returns `None` if the panel is closed or the press is outside it, for the scene underneath,
+    /// otherwise whether a setting changed.
+    #[cfg(feature = "gui")]
+    pub fn press_panel(&mut self, settings: &mut Settings) -> Option<bool> {
+        let kind = self.scenes[self.active].kind();
+        if !self.show_panel || !panel::covers(&self.layout, settings, kind, self.cursor) {
+            return None;
+        }
+        // The scenes' speeds and options are theirs while running, the panel starts from them
+        self.save(settings);
+        let field = panel::press(&self.layout, settings, kind, self.cursor);
+        if let Some(field) = field {
+            info!("Settings panel: {field:?}");
+            self.slider = field.slides().then_some(field);
+        }
+        Some(field.is_some())
+    }
+
+    /// Hand what the settings panel wrote into the settings to the scenes, and show it.
+    /// New colors fade in like a theme change.
+    #[cfg(feature = "gui")]
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        for scene in &mut self.scenes {
+            scene.apply_settings(settings);
+        }
+        self.fade_to(settings);
+        self.next_frame = Instant::now();
+        self.window.set_window_level(crate::window_level(settings.always_on_top));
+        self.window.set_decorations(!settings.borderless);
+        self.window.request_redraw();
+    }
+
+    /// Freeze the active scene or let it run again.
+    ///
+    /// On resume the scene starts over from the present, it doesn't continue from where it stopped:
+    /// the watch jumps to the current time and the metronome restarts on a downbeat (see `Scene::activate`).
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        info!("{}", if self.paused { "Paused" } else { "Resumed" });
+        if !self.paused {
+            let now = Instant::now();
+            self.scene().activate(now);
+            self.next_frame = now;
+        }
+    }
+
+    /// Restart the active scene from the present and show that right away.
+    pub fn reset_scene(&mut self) {
+        let now = Instant::now();
+        let scene = self.scene();
+        scene.reset(now);
+        // Also while paused, the frozen frame shows the reset state
+        scene.update(&SystemClock);
+        self.next_frame = now;
+        self.window.request_redraw();
+    }
+
+    /// Show the next scene, only does something when more than one is built in.
+    pub fn switch_scene(&mut self, settings: &Settings) {
+        if self.scenes.len() < 2 {
+            return;
+        }
+        self.active = (self.active + 1) % self.scenes.len();
+        // The pause is for the scene it was pressed in, the other one shows up running
+        self.paused = false;
+        let now = Instant::now();
+        let scene = &mut self.scenes[self.active];
+        scene.activate(now);
+        // A theme fade in progress was into the other scene's colors, this one starts at its own
+        self.palette = settings.palette(scene.kind());
+        self.theme_transition = None;
+        info!("Scene: {:?}", scene.kind());
+
+        // Draw the new scene right away, its own pacing takes over from there
+        self.next_frame = now;
+        self.window.set_title(scene.kind().title());
+        self.window.request_redraw();
+    }
+}
+
+/// The earliest of the `times` that are set, `None` if none is.
+fn earliest<const N: usize>(times: [Option<Instant>; N]) -> Option<Instant> {
+    times.into_iter().flatten().min()
+}
+
+/// Surface size for a window size, or `None` if either side is 0 (softbuffer needs non-zero sizes).
+pub fn clamp_surface_size(width: u32, height: u32) -> Option<(NonZeroU32, NonZeroU32)> {
+    Some((NonZeroU32::new(width)?, NonZeroU32::new(height)?))
+}
+
+/// Softbuffer damage rect for a canvas rect, `None` if it is empty.
+fn damage_rect(rect: draw::Rect) -> Option<softbuffer::Rect> {
+    Some(softbuffer::Rect {
+        x: rect.x0 as u32,
+        y: rect.y0 as u32,
+        width: NonZeroU32::new(rect.width() as u32)?,
+        height: NonZeroU32::new(rect.height() as u32)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_surface_size_skips_a_zero_side() {
+        assert_eq!(clamp_surface_size(0, 600), None);
+        assert_eq!(clamp_surface_size(800, 0), None);
+        assert_eq!(clamp_surface_size(0, 0), None);
+    }
+
+    #[test]
+    fn clamp_surface_size_keeps_a_nonzero_size() {
+        let size = clamp_surface_size(800, 600).map(|(w, h)| (w.get(), h.get()));
+        assert_eq!(size, Some((800, 600)));
+        let size = clamp_surface_size(1, 1).map(|(w, h)| (w.get(), h.get()));
+        assert_eq!(size, Some((1, 1)));
+    }
+}
@@ -0,0 +1,138 @@
+// The world clock scene: a grid of small watches, one per time zone, each with the city under it.
+//
+// The zones come from --world or the settings. Every cell is a scaled-down watch drawn with the
+// watch's own dial and hands, through a layout centered on the cell (see Layout::grid).
+// Like the stopwatch, the dials are drawn once into a background snapshot and all of it is copied
+// back each frame: with a handful of clocks, most of the window has hands moving over it anyway.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use log::warn;
+use winit::keyboard::Key;
+
+use crate::clock::{self, TimeOfDay};
+use crate::config::{SecondsDisplay, SecondsMode, Settings};
+use watchrs::draw::{Canvas, Point, Rect};
+use watchrs::font;
+use crate::hud::Hud;
+use crate::layout::{Layout, WorldCell};
+use crate::scene::{Scene, SceneKind};
+use crate::theme::ThemePalette;
+use crate::time_source::{SystemClock, TimeSource};
+use crate::watch;
+
+// The seconds hands tick, a few redraws a second keep them within a fraction of a second of the time
+const FPS: u32 = 4;
+
+/// Small clocks for several time zones.
+pub struct WorldScene {
+    zones:      Vec<Tz>,
+    labels:     Vec<String>,        // city names, one per zone
+    columns:    usize,              // clocks per row
+    twelve_hour: bool,
+    wall:       DateTime<Utc>,      // the time the clocks show, each in its zone
+    cells:      Vec<WorldCell>,     // one per zone, for the window size in cells_size
+    cells_size: (usize, usize),
+    background: Vec<u32>,           // the dials and labels drawn once, empty when they need a redraw
+    background_look: Option<(usize, usize, ThemePalette)>, // size and colors the background was drawn with
+}
+
+impl WorldScene {
+    /// Clocks for the IANA zone `names`, names that don't parse are reported and left out.
+    pub fn new(names: &[String], settings: &Settings) -> Self {
+        let zones: Vec<Tz> = names
+            .iter()
+            .filter_map(|name| match name.parse() {
+                Ok(zone) => Some(zone),
+                Err(_) => {
+                    warn!("Unknown time zone '{name}', leaving it out of the world clock");
+                    None
+                }
+            })
+            .collect();
+        // As many columns as rows, or one more, unless the settings choose
+        let columns = settings.world_columns.unwrap_or_else(|| (zones.len() as f32).sqrt().ceil() as usize);
+        Self {
+            labels: zones.iter().map(|zone| city(zone.name())).collect(),
+            zones,
+            columns,
+            twelve_hour: settings.twelve_hour.unwrap_or_else(clock::locale_prefers_12_hour),
+            wall: SystemClock.wall(),
+            cells: Vec::new(),
+            cells_size: (0, 0),
+            background: Vec::new(),
+            background_look: None,
+        }
+    }
+}
+
+impl Scene for WorldScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::World
+    }
+
+    fn frame_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(1.0 / FPS as f32))
+    }
+
+    // The time in each zone is all there is
+    fn reset(&mut self, _now: Instant) {}
+
+    fn update(&mut self, time: &dyn TimeSource) {
+        self.wall = time.wall();
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, layout: &Layout, palette: &ThemePalette, _buffer_age: usize, hud: &Hud) -> Option<Vec<Rect>> {
+        if self.cells_size != (layout.width, layout.height) {
+            self.cells = layout.grid(self.zones.len(), self.columns);
+            self.cells_size = (layout.width, layout.height);
+        }
+
+        let look = Some((layout.width, layout.height, *palette));
+        if self.background.is_empty() || self.background_look != look {
+            self.background = vec![0; canvas.buf.len()];
+            let mut background = Canvas::new(&mut self.background, layout.dimensions())
+                .expect("background is allocated at the layout size");
+            palette.fill_background(&mut background);
+            background.draw_frame(layout.frame_padding, layout.thick, palette.foreground);
+            for (cell, label) in self.cells.iter().zip(&self.labels) {
+                watch::draw_dial(&mut background, &cell.layout, palette, None);
+                draw_label(&mut background, cell, label, palette.foreground);
+            }
+            self.background_look = look;
+        }
+        canvas.buf.copy_from_slice(&self.background);
+
+        for (cell, &zone) in self.cells.iter().zip(&self.zones) {
+            watch::draw_hands(canvas, &cell.layout, palette, TimeOfDay::at(self.wall, Some(zone)), SecondsMode::Tick, SecondsDisplay::Hand, self.twelve_hour, None);
+        }
+        hud.draw(canvas, layout, palette);
+        None
+    }
+
+    fn key(&mut self, _key: &Key) {}
+
+    // Nothing to speed up
+    fn change_speed(&mut self, _steps: i32) {}
+
+    fn save(&self, settings: &mut Settings) {
+        settings.world_zones = self.zones.iter().map(|zone| zone.name().to_string()).collect();
+    }
+}
+
+/// The city part of a zone name, "America/New_York" -> "New York".
+fn city(zone: &str) -> String {
+    zone.rsplit('/').next().unwrap_or(zone).replace('_', " ")
+}
+
+/// `label` centered under the cell's dial, scaled down until it fits the cell's width.
+fn draw_label(canvas: &mut Canvas, cell: &WorldCell, label: &str, color: u32) {
+    let scale = (1..=cell.label_scale)
+        .rev()
+        .find(|&scale| font::text_width(label, scale) <= cell.label_width)
+        .unwrap_or(1);
+    let origin = Point::new(cell.label_center.x - (font::text_width(label, scale) / 2) as isize, cell.label_center.y);
+    canvas.draw_text(origin, label, scale, color);
+}